@@ -16,7 +16,13 @@ fn main() {
     dest = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
 
     let mut file = File::create(dest.join("gl_bindings.rs")).unwrap();
-    Registry::new(Api::Gles2, (3, 3), Profile::Core, Fallbacks::All, [])
-        .write_bindings(gl_generator::StructGenerator, &mut file)
-        .unwrap();
+    Registry::new(
+        Api::Gles2,
+        (3, 3),
+        Profile::Core,
+        Fallbacks::All,
+        ["GL_EXT_texture_filter_anisotropic"],
+    )
+    .write_bindings(gl_generator::StructGenerator, &mut file)
+    .unwrap();
 }
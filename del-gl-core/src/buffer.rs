@@ -0,0 +1,214 @@
+//! owned buffer and vertex array objects deleted on drop.
+//! Each handle shares the function table through `Rc` so that it can delete itself.
+
+use crate::gl;
+use std::rc::Rc;
+
+/// usage hint of the buffer data. The drawers take it for their vertex buffers
+/// (e.g., [`crate::drawer_mesh::Drawer::usage`]), so choose `Dynamic` or `Stream`
+/// for the vertices updated every frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BufferUsage {
+    /// set once and drawn many times
+    #[default]
+    Static,
+    /// updated repeatedly and drawn many times
+    Dynamic,
+    /// updated every frame and drawn a few times
+    Stream,
+}
+
+impl BufferUsage {
+    pub fn to_gl(self) -> gl::types::GLenum {
+        match self {
+            BufferUsage::Static => gl::STATIC_DRAW,
+            BufferUsage::Dynamic => gl::DYNAMIC_DRAW,
+            BufferUsage::Stream => gl::STREAM_DRAW,
+        }
+    }
+}
+
+pub struct Buffer {
+    gl: Rc<gl::Gl>,
+    id: gl::types::GLuint,
+    target: gl::types::GLenum,
+    /// size of the data store in bytes
+    size: usize,
+    usage: BufferUsage,
+}
+
+impl Buffer {
+    /// generate a buffer bound to `target` (e.g., `gl::ARRAY_BUFFER`)
+    pub fn new(gl: &Rc<gl::Gl>, target: gl::types::GLenum) -> Self {
+        let mut id = 0;
+        unsafe {
+            gl.GenBuffers(1, &mut id);
+        }
+        Buffer {
+            gl: Rc::clone(gl),
+            id,
+            target,
+            size: 0,
+            usage: BufferUsage::Static,
+        }
+    }
+
+    pub fn id(&self) -> gl::types::GLuint {
+        self.id
+    }
+
+    pub fn target(&self) -> gl::types::GLenum {
+        self.target
+    }
+
+    /// name shown in the driver messages (see `crate::debug`).
+    /// The buffer needs to be bound once before
+    pub fn set_label(&self, label: &str) {
+        crate::debug::object_label(&self.gl, gl::BUFFER, self.id, label);
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            self.gl.BindBuffer(self.target, self.id);
+        }
+    }
+
+    /// size of the data store in bytes
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn usage(&self) -> BufferUsage {
+        self.usage
+    }
+
+    /// bind the buffer and upload the data. If the size and the usage are unchanged,
+    /// the data store is updated in place with `BufferSubData`. Otherwise it is reallocated.
+    /// A `Stream` buffer is orphaned before the update so that the draw calls
+    /// still reading the old data do not stall the upload.
+    pub fn set_data<T: Copy>(&mut self, data: &[T], usage: BufferUsage) {
+        let size = std::mem::size_of_val(data);
+        self.bind();
+        unsafe {
+            if size == self.size && usage == self.usage && size != 0 {
+                if usage == BufferUsage::Stream {
+                    self.gl.BufferData(
+                        self.target,
+                        size as gl::types::GLsizeiptr,
+                        std::ptr::null(),
+                        usage.to_gl(),
+                    );
+                }
+                self.gl.BufferSubData(
+                    self.target,
+                    0,
+                    size as gl::types::GLsizeiptr,
+                    data.as_ptr() as *const _,
+                );
+            } else {
+                self.gl.BufferData(
+                    self.target,
+                    size as gl::types::GLsizeiptr,
+                    data.as_ptr() as *const _,
+                    usage.to_gl(),
+                );
+                self.size = size;
+                self.usage = usage;
+            }
+        }
+        crate::debug::check_error(&self.gl, "uploading buffer data");
+    }
+
+    /// bind the buffer and upload the vertex indices. Returns the index type for `DrawElements`.
+    /// The `u8`, `u16` and `u32` indices are uploaded as they are. The other types are
+    /// converted to `u16` if all the indices fit in it, or to `u32` otherwise.
+    pub fn set_indices<T>(&mut self, elem2vtx: &[T], usage: BufferUsage) -> gl::types::GLenum
+    where
+        T: 'static + Copy + num_traits::AsPrimitive<gl::types::GLuint>,
+    {
+        use std::any::TypeId;
+        let type_id = TypeId::of::<T>();
+        if type_id == TypeId::of::<u32>() {
+            self.set_data(elem2vtx, usage);
+            return gl::UNSIGNED_INT;
+        }
+        if type_id == TypeId::of::<u16>() {
+            self.set_data(elem2vtx, usage);
+            return gl::UNSIGNED_SHORT;
+        }
+        if type_id == TypeId::of::<u8>() {
+            self.set_data(elem2vtx, usage);
+            return gl::UNSIGNED_BYTE;
+        }
+        let max_index = elem2vtx.iter().map(|i| i.as_()).max().unwrap_or(0);
+        // 0xffff is avoided because it is the primitive restart index of u16
+        if max_index < u16::MAX as gl::types::GLuint {
+            let elem2vtx: Vec<u16> = elem2vtx.iter().map(|i| i.as_() as u16).collect();
+            self.set_data(&elem2vtx, usage);
+            gl::UNSIGNED_SHORT
+        } else {
+            let elem2vtx: Vec<gl::types::GLuint> = elem2vtx.iter().map(|i| i.as_()).collect();
+            self.set_data(&elem2vtx, usage);
+            gl::UNSIGNED_INT
+        }
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteBuffers(1, &self.id);
+        }
+    }
+}
+
+/// vertex array object. On a context without VAO (e.g., OpenGL ES 2.0),
+/// the handle is empty and `bind` does nothing.
+pub struct VertexArray {
+    gl: Rc<gl::Gl>,
+    id: gl::types::GLuint,
+}
+
+impl VertexArray {
+    pub fn new(gl: &Rc<gl::Gl>) -> Self {
+        let mut id = 0;
+        if gl.GenVertexArrays.is_loaded() {
+            unsafe {
+                gl.GenVertexArrays(1, &mut id);
+            }
+        }
+        VertexArray {
+            gl: Rc::clone(gl),
+            id,
+        }
+    }
+
+    /// zero if the VAO is not supported
+    pub fn id(&self) -> gl::types::GLuint {
+        self.id
+    }
+
+    /// name shown in the driver messages (see `crate::debug`).
+    /// The VAO needs to be bound once before
+    pub fn set_label(&self, label: &str) {
+        crate::debug::object_label(&self.gl, gl::VERTEX_ARRAY, self.id, label);
+    }
+
+    pub fn bind(&self) {
+        if self.id != 0 {
+            unsafe {
+                self.gl.BindVertexArray(self.id);
+            }
+        }
+    }
+}
+
+impl Drop for VertexArray {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            unsafe {
+                self.gl.DeleteVertexArrays(1, &self.id);
+            }
+        }
+    }
+}
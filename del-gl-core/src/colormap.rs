@@ -0,0 +1,271 @@
+//! color tables for `drawer_mesh_colormap::Drawer::color_map`.
+//!
+//! The presets are sampled at `NUM_STOP` evenly spaced stops, which are linearly interpolated
+//! in the fragment shader. The number of stops is kept small because the table is
+//! a uniform array (at least 16 vectors are available in the GLES 2.0 fragment shader).
+//!
+//! ```text
+//! drawer.color_map = colormap::Preset::Viridis.colors();
+//! drawer.color_map = colormap::from_name("coolwarm_r")?;
+//! drawer.color_map = colormap::load_csv("asset/colormap.csv")?;
+//! ```
+
+/// perceptually uniform maps of matplotlib (viridis, plasma, inferno, magma and cividis),
+/// rainbow maps and diverging maps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Preset {
+    Viridis,
+    Plasma,
+    Inferno,
+    Magma,
+    /// viridis adjusted for the color vision deficiency
+    Cividis,
+    /// improved rainbow of Google (polynomial approximation at the inner stops)
+    Turbo,
+    /// rainbow of MATLAB, which is not perceptually uniform
+    Jet,
+    /// diverging map of Moreland with a gray center
+    Coolwarm,
+    Grayscale,
+    /// blue, white and red diverging around the middle of the range
+    Diverging,
+}
+
+/// number of the stops of every preset, so that the presets are switched
+/// without recompiling the shader of the drawer
+pub const NUM_STOP: usize = 9;
+
+#[rustfmt::skip]
+const VIRIDIS: [[u8; 3]; NUM_STOP] = [
+    [68, 1, 84], [71, 45, 123], [59, 82, 139], [44, 114, 142], [33, 145, 140],
+    [40, 174, 128], [94, 201, 98], [173, 220, 48], [253, 231, 37],
+];
+
+#[rustfmt::skip]
+const PLASMA: [[u8; 3]; NUM_STOP] = [
+    [13, 8, 135], [76, 2, 161], [126, 3, 168], [169, 35, 149], [204, 71, 120],
+    [229, 107, 93], [248, 149, 64], [253, 195, 40], [240, 249, 33],
+];
+
+#[rustfmt::skip]
+const INFERNO: [[u8; 3]; NUM_STOP] = [
+    [0, 0, 4], [31, 12, 72], [85, 15, 109], [136, 34, 106], [186, 54, 85],
+    [227, 89, 51], [249, 142, 9], [249, 203, 53], [252, 255, 164],
+];
+
+#[rustfmt::skip]
+const MAGMA: [[u8; 3]; NUM_STOP] = [
+    [0, 0, 4], [28, 16, 68], [79, 18, 123], [129, 37, 129], [181, 54, 122],
+    [229, 80, 100], [251, 135, 97], [254, 194, 135], [252, 253, 191],
+];
+
+#[rustfmt::skip]
+const CIVIDIS: [[u8; 3]; NUM_STOP] = [
+    [0, 34, 78], [18, 53, 112], [59, 73, 108], [87, 93, 109], [112, 113, 115],
+    [138, 134, 120], [165, 156, 116], [195, 179, 105], [254, 232, 56],
+];
+
+#[rustfmt::skip]
+const TURBO: [[u8; 3]; NUM_STOP] = [
+    [48, 18, 59], [68, 106, 238], [38, 189, 225], [64, 243, 146], [150, 250, 80],
+    [238, 208, 45], [255, 128, 29], [201, 45, 12], [122, 4, 3],
+];
+
+#[rustfmt::skip]
+const JET: [[u8; 3]; NUM_STOP] = [
+    [0, 0, 128], [0, 0, 255], [0, 128, 255], [0, 255, 255], [128, 255, 128],
+    [255, 255, 0], [255, 128, 0], [255, 0, 0], [128, 0, 0],
+];
+
+#[rustfmt::skip]
+const COOLWARM: [[u8; 3]; NUM_STOP] = [
+    [59, 76, 192], [98, 130, 234], [141, 176, 254], [184, 208, 249], [221, 221, 221],
+    [245, 196, 173], [244, 154, 123], [222, 96, 77], [180, 4, 38],
+];
+
+#[rustfmt::skip]
+const GRAYSCALE: [[u8; 3]; NUM_STOP] = [
+    [0, 0, 0], [32, 32, 32], [64, 64, 64], [96, 96, 96], [128, 128, 128],
+    [159, 159, 159], [191, 191, 191], [223, 223, 223], [255, 255, 255],
+];
+
+#[rustfmt::skip]
+const DIVERGING: [[u8; 3]; NUM_STOP] = [
+    [0, 0, 255], [64, 64, 255], [128, 128, 255], [191, 191, 255], [255, 255, 255],
+    [255, 191, 191], [255, 128, 128], [255, 64, 64], [255, 0, 0],
+];
+
+impl Preset {
+    pub const ALL: [Preset; 10] = [
+        Preset::Viridis,
+        Preset::Plasma,
+        Preset::Inferno,
+        Preset::Magma,
+        Preset::Cividis,
+        Preset::Turbo,
+        Preset::Jet,
+        Preset::Coolwarm,
+        Preset::Grayscale,
+        Preset::Diverging,
+    ];
+
+    /// lowercase name accepted by `from_name`
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Viridis => "viridis",
+            Preset::Plasma => "plasma",
+            Preset::Inferno => "inferno",
+            Preset::Magma => "magma",
+            Preset::Cividis => "cividis",
+            Preset::Turbo => "turbo",
+            Preset::Jet => "jet",
+            Preset::Coolwarm => "coolwarm",
+            Preset::Grayscale => "grayscale",
+            Preset::Diverging => "diverging",
+        }
+    }
+
+    fn stops(self) -> &'static [[u8; 3]] {
+        match self {
+            Preset::Viridis => &VIRIDIS,
+            Preset::Plasma => &PLASMA,
+            Preset::Inferno => &INFERNO,
+            Preset::Magma => &MAGMA,
+            Preset::Cividis => &CIVIDIS,
+            Preset::Turbo => &TURBO,
+            Preset::Jet => &JET,
+            Preset::Coolwarm => &COOLWARM,
+            Preset::Grayscale => &GRAYSCALE,
+            Preset::Diverging => &DIVERGING,
+        }
+    }
+
+    /// RGB in [0, 1] from the minimum to the maximum value
+    pub fn colors(self) -> Vec<[f32; 3]> {
+        self.stops()
+            .iter()
+            .map(|rgb| rgb.map(|c| c as f32 / 255.0))
+            .collect()
+    }
+
+    /// colors from the maximum to the minimum value
+    pub fn colors_reversed(self) -> Vec<[f32; 3]> {
+        reversed(&self.colors())
+    }
+}
+
+impl std::str::FromStr for Preset {
+    type Err = ColormapError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Preset::ALL
+            .into_iter()
+            .find(|p| p.name() == name)
+            .ok_or_else(|| ColormapError::UnknownName(name.to_string()))
+    }
+}
+
+#[derive(Debug)]
+pub enum ColormapError {
+    Io(std::io::Error),
+    /// a line of the CSV is not three numbers in [0, 1]
+    Parse {
+        line: usize,
+        content: String,
+    },
+    /// the interpolation needs two colors at least
+    TooFewColors(usize),
+    UnknownName(String),
+}
+
+impl std::fmt::Display for ColormapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColormapError::Io(e) => write!(f, "{e}"),
+            ColormapError::Parse { line, content } => {
+                write!(f, "line {line} is not an RGB in [0, 1]: {content:?}")
+            }
+            ColormapError::TooFewColors(num) => {
+                write!(f, "{num} colors are too few for a colormap")
+            }
+            ColormapError::UnknownName(name) => write!(f, "unknown colormap {name:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ColormapError {}
+
+impl From<std::io::Error> for ColormapError {
+    fn from(e: std::io::Error) -> Self {
+        ColormapError::Io(e)
+    }
+}
+
+pub fn reversed(colors: &[[f32; 3]]) -> Vec<[f32; 3]> {
+    colors.iter().rev().copied().collect()
+}
+
+/// `num` colors evenly spaced on the piecewise linear map of `colors`
+pub fn resample(colors: &[[f32; 3]], num: usize) -> Vec<[f32; 3]> {
+    if colors.len() < 2 || num < 2 {
+        return vec![colors.first().copied().unwrap_or([0.0; 3]); num];
+    }
+    (0..num)
+        .map(|i| {
+            let t = i as f32 * (colors.len() - 1) as f32 / (num - 1) as f32;
+            let idx = (t as usize).min(colors.len() - 2);
+            let r = t - idx as f32;
+            let (c0, c1) = (colors[idx], colors[idx + 1]);
+            std::array::from_fn(|k| (1.0 - r) * c0[k] + r * c1[k])
+        })
+        .collect()
+}
+
+/// colors of the preset `name` (e.g., "viridis"), reversed if the name ends with `_r`
+pub fn from_name(name: &str) -> Result<Vec<[f32; 3]>, ColormapError> {
+    match name.strip_suffix("_r") {
+        Some(base) => Ok(base.parse::<Preset>()?.colors_reversed()),
+        None => Ok(name.parse::<Preset>()?.colors()),
+    }
+}
+
+/// parse the evenly spaced stops written as `r, g, b` in [0, 1] per line.
+/// The empty lines, the lines starting with `#` and a header line (e.g., `r,g,b`) are skipped
+pub fn from_csv(text: &str) -> Result<Vec<[f32; 3]>, ColormapError> {
+    let mut colors = vec![];
+    for (iline, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let is_header = colors.is_empty() && line.starts_with(|c: char| c.is_alphabetic());
+        if is_header {
+            continue;
+        }
+        let values: Vec<f32> = line
+            .split(',')
+            .map(|s| s.trim().parse::<f32>())
+            .collect::<Result<_, _>>()
+            .unwrap_or_default();
+        match values[..] {
+            [r, g, b] if [r, g, b].iter().all(|c| (0.0..=1.0).contains(c)) => {
+                colors.push([r, g, b])
+            }
+            _ => {
+                return Err(ColormapError::Parse {
+                    line: iline + 1,
+                    content: line.to_string(),
+                })
+            }
+        }
+    }
+    if colors.len() < 2 {
+        return Err(ColormapError::TooFewColors(colors.len()));
+    }
+    Ok(colors)
+}
+
+/// read the CSV file of the stops (see `from_csv`)
+pub fn load_csv<P: AsRef<std::path::Path>>(path: P) -> Result<Vec<[f32; 3]>, ColormapError> {
+    from_csv(&std::fs::read_to_string(path)?)
+}
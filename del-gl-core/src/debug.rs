@@ -0,0 +1,237 @@
+//! opt-in reporting of the GL errors and driver messages through the `log` crate.
+//!
+//! `enable` installs a message callback if the context supports `GL_KHR_debug` or
+//! `GL_ARB_debug_output` (create the context with the debug flag for the full report).
+//! Otherwise, `check_error` polls `GetError` after the calls wrapped in this crate.
+//! Both do nothing until `enable` is called, so the release builds pay only for
+//! a thread-local flag. The mode is per thread, like the current context.
+
+use crate::gl;
+use std::cell::Cell;
+use std::ffi::CStr;
+
+/// severity of the driver messages. The messages below the minimum are discarded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Notification,
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    pub fn to_gl(self) -> gl::types::GLenum {
+        match self {
+            Severity::Notification => gl::DEBUG_SEVERITY_NOTIFICATION,
+            Severity::Low => gl::DEBUG_SEVERITY_LOW,
+            Severity::Medium => gl::DEBUG_SEVERITY_MEDIUM,
+            Severity::High => gl::DEBUG_SEVERITY_HIGH,
+        }
+    }
+
+    pub fn from_gl(severity: gl::types::GLenum) -> Self {
+        match severity {
+            gl::DEBUG_SEVERITY_HIGH => Severity::High,
+            gl::DEBUG_SEVERITY_MEDIUM => Severity::Medium,
+            gl::DEBUG_SEVERITY_LOW => Severity::Low,
+            _ => Severity::Notification,
+        }
+    }
+
+    fn log_level(self) -> log::Level {
+        match self {
+            Severity::High => log::Level::Error,
+            Severity::Medium => log::Level::Warn,
+            Severity::Low => log::Level::Info,
+            Severity::Notification => log::Level::Debug,
+        }
+    }
+}
+
+/// how the errors are reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// nothing is reported (default)
+    Off,
+    /// the driver calls the message callback
+    Callback,
+    /// `check_error` polls `GetError`
+    GetError,
+}
+
+thread_local! {
+    static MODE: Cell<Mode> = const { Cell::new(Mode::Off) };
+}
+
+/// current mode of this thread
+pub fn mode() -> Mode {
+    MODE.with(|m| m.get())
+}
+
+/// check if the context can report the messages through a callback
+pub fn is_callback_supported(gl: &gl::Gl) -> bool {
+    gl.DebugMessageCallback.is_loaded()
+        && gl.DebugMessageControl.is_loaded()
+        && (crate::has_extension(gl, "GL_KHR_debug")
+            || crate::has_extension(gl, "GL_ARB_debug_output"))
+}
+
+fn source_name(source: gl::types::GLenum) -> &'static str {
+    match source {
+        gl::DEBUG_SOURCE_API => "api",
+        gl::DEBUG_SOURCE_WINDOW_SYSTEM => "window system",
+        gl::DEBUG_SOURCE_SHADER_COMPILER => "shader compiler",
+        gl::DEBUG_SOURCE_THIRD_PARTY => "third party",
+        gl::DEBUG_SOURCE_APPLICATION => "application",
+        _ => "other",
+    }
+}
+
+fn type_name(type_: gl::types::GLenum) -> &'static str {
+    match type_ {
+        gl::DEBUG_TYPE_ERROR => "error",
+        gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "deprecated",
+        gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "undefined behavior",
+        gl::DEBUG_TYPE_PORTABILITY => "portability",
+        gl::DEBUG_TYPE_PERFORMANCE => "performance",
+        gl::DEBUG_TYPE_MARKER => "marker",
+        _ => "other",
+    }
+}
+
+/// name of the error code returned by `GetError`
+pub fn error_name(error: gl::types::GLenum) -> &'static str {
+    match error {
+        gl::NO_ERROR => "NO_ERROR",
+        gl::INVALID_ENUM => "INVALID_ENUM",
+        gl::INVALID_VALUE => "INVALID_VALUE",
+        gl::INVALID_OPERATION => "INVALID_OPERATION",
+        gl::INVALID_FRAMEBUFFER_OPERATION => "INVALID_FRAMEBUFFER_OPERATION",
+        gl::OUT_OF_MEMORY => "OUT_OF_MEMORY",
+        _ => "unknown error",
+    }
+}
+
+extern "system" fn message_callback(
+    source: gl::types::GLenum,
+    type_: gl::types::GLenum,
+    id: gl::types::GLuint,
+    severity: gl::types::GLenum,
+    length: gl::types::GLsizei,
+    message: *const gl::types::GLchar,
+    _user_param: *mut std::ffi::c_void,
+) {
+    let message = if message.is_null() {
+        "".into()
+    } else if length < 0 {
+        unsafe { CStr::from_ptr(message) }.to_string_lossy()
+    } else {
+        let bytes = unsafe { std::slice::from_raw_parts(message.cast::<u8>(), length as usize) };
+        String::from_utf8_lossy(bytes)
+    };
+    // the errors are always reported as errors regardless of the severity
+    let level = if type_ == gl::DEBUG_TYPE_ERROR {
+        log::Level::Error
+    } else {
+        Severity::from_gl(severity).log_level()
+    };
+    log::log!(
+        level,
+        "GL {} {} ({:#x}): {}",
+        source_name(source),
+        type_name(type_),
+        id,
+        message.trim_end()
+    );
+}
+
+/// start reporting the messages of `min_severity` or above. The context of `gl`
+/// needs to be current. Returns the mode used
+pub fn enable(gl: &gl::Gl, min_severity: Severity) -> Mode {
+    let mode = if is_callback_supported(gl) {
+        unsafe {
+            // `ARB_debug_output` does not know `DEBUG_OUTPUT` and raises `INVALID_ENUM`
+            gl.Enable(gl::DEBUG_OUTPUT);
+            gl.Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+            gl.DebugMessageCallback(Some(message_callback), std::ptr::null());
+            gl.DebugMessageControl(
+                gl::DONT_CARE,
+                gl::DONT_CARE,
+                gl::DONT_CARE,
+                0,
+                std::ptr::null(),
+                gl::TRUE,
+            );
+            for severity in [Severity::Notification, Severity::Low, Severity::Medium] {
+                if severity < min_severity {
+                    gl.DebugMessageControl(
+                        gl::DONT_CARE,
+                        gl::DONT_CARE,
+                        severity.to_gl(),
+                        0,
+                        std::ptr::null(),
+                        gl::FALSE,
+                    );
+                }
+            }
+            for _ in 0..16 {
+                if gl.GetError() == gl::NO_ERROR {
+                    break;
+                }
+            }
+        }
+        Mode::Callback
+    } else {
+        log::warn!("GL debug output is not supported. GetError is checked instead");
+        Mode::GetError
+    };
+    MODE.with(|m| m.set(mode));
+    mode
+}
+
+/// stop reporting. The callback stays installed but is not called
+pub fn disable(gl: &gl::Gl) {
+    if mode() == Mode::Callback {
+        unsafe {
+            gl.Disable(gl::DEBUG_OUTPUT);
+        }
+    }
+    MODE.with(|m| m.set(Mode::Off));
+}
+
+/// log the errors raised since the last check if the mode is `GetError`.
+/// `what` describes the calls checked (e.g., "DrawElements in drawer_mesh")
+pub fn check_error(gl: &gl::Gl, what: &str) {
+    if mode() != Mode::GetError {
+        return;
+    }
+    // a lost context may return an error forever
+    for _ in 0..16 {
+        let error = unsafe { gl.GetError() };
+        if error == gl::NO_ERROR {
+            break;
+        }
+        log::error!("GL {} ({:#x}) after {}", error_name(error), error, what);
+    }
+}
+
+/// give `label` to the object `name` of type `identifier` (e.g., `gl::BUFFER`)
+/// shown in the driver messages. Does nothing unless the mode is `Callback`
+pub fn object_label(
+    gl: &gl::Gl,
+    identifier: gl::types::GLenum,
+    name: gl::types::GLuint,
+    label: &str,
+) {
+    if mode() != Mode::Callback || !gl.ObjectLabel.is_loaded() || name == 0 {
+        return;
+    }
+    unsafe {
+        gl.ObjectLabel(
+            identifier,
+            name,
+            label.len() as gl::types::GLsizei,
+            label.as_ptr().cast(),
+        );
+    }
+}
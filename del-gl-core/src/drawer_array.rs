@@ -1,28 +1,16 @@
 //! draw array of position and color
 
+use crate::buffer::{Buffer, BufferUsage, VertexArray};
 use crate::gl;
+use crate::shader_program::{ShaderProgram, ShaderSource};
+use crate::vertex_layout::VertexLayout;
+use std::rc::Rc;
 
-pub struct Drawer {
-    pub program: gl::types::GLuint,
-    pub mode: gl::types::GLenum,
-    pub elem_size: usize,
-    pub vao: gl::types::GLuint,
-    pub loc_color: gl::types::GLint,
-    pub loc_mat_modelview: gl::types::GLint,
-    pub loc_mat_projection: gl::types::GLint,
-    pub color: [f32; 3],
-}
-
-impl Drawer {
-    pub fn compile_shader(&mut self, gl: &gl::Gl) {
-        const VS_SRC: &[u8] = b"
-#version 100
-precision mediump float;
-
+const VS_SRC: &[u8] = b"
 uniform mat4 matMV;
 uniform mat4 matPrj;
 
-attribute vec3 position;
+in vec3 position;
 
 void main() {
 //    gl_Position = vec4(position, 1.0);
@@ -30,58 +18,59 @@ void main() {
 }
 \0";
 
-        const FS_SRC: &[u8] = b"
-#version 100
-precision mediump float;
-
+const FS_SRC: &[u8] = b"
 uniform vec3 color;
 
+out vec4 FragColor;
+
 void main() {
-    gl_FragColor = vec4(color, 1.0);
-    // gl_FragColor = vec4(0., 1., 0., 1.0);
+    FragColor = vec4(color, 1.0);
+    // FragColor = vec4(0., 1., 0., 1.0);
 }
 \0";
-        use crate::utility::{compile_shaders, get_uniform_location};
-        unsafe {
-            self.program = compile_shaders(gl, VS_SRC, FS_SRC);
-            self.loc_mat_modelview = get_uniform_location(gl, "matMV", self.program);
-            self.loc_mat_projection = get_uniform_location(gl, "matPrj", self.program);
-            self.loc_color = get_uniform_location(gl, "color", self.program);
+
+pub struct Drawer {
+    pub program: ShaderProgram,
+    pub mode: gl::types::GLenum,
+    pub elem_size: usize,
+    pub vao: Option<VertexArray>,
+    pub vbo: Option<Buffer>,
+    pub color: [f32; 3],
+}
+
+impl Drawer {
+    pub fn new(mode: gl::types::GLenum, color: [f32; 3]) -> Self {
+        Drawer {
+            program: ShaderProgram::new(
+                ShaderSource::from_bytes(VS_SRC),
+                ShaderSource::from_bytes(FS_SRC),
+            ),
+            mode,
+            elem_size: 0,
+            vao: None,
+            vbo: None,
+            color,
         }
     }
 
-    pub fn initialize(&mut self, gl: &gl::Gl, vtx2xyz: &Vec<f32>) {
-        unsafe {
-            let mut vb = 0;
-            gl.GenBuffers(1, &mut vb);
-            gl.BindBuffer(gl::ARRAY_BUFFER, vb);
-            gl.BufferData(
-                gl::ARRAY_BUFFER,
-                (vtx2xyz.len() * std::mem::size_of::<f32>()) as gl::types::GLsizeiptr,
-                vtx2xyz.as_ptr() as *const _,
-                gl::STATIC_DRAW,
-            );
-
-            if gl.BindVertexArray.is_loaded() {
-                self.vao = std::mem::zeroed();
-                gl.GenVertexArrays(1, &mut self.vao);
-                gl.BindVertexArray(self.vao);
-            }
+    pub fn compile_shader(&mut self, gl: &Rc<gl::Gl>) -> Result<(), crate::ShaderError> {
+        self.program.compile(gl)
+    }
 
-            let pos_attrib = gl.GetAttribLocation(self.program, b"position\0".as_ptr() as *const _);
-            gl.VertexAttribPointer(
-                pos_attrib as gl::types::GLuint,
-                3,
-                gl::FLOAT,
-                0,
-                3 * std::mem::size_of::<f32>() as gl::types::GLsizei,
-                std::ptr::null(),
-            );
-            gl.EnableVertexAttribArray(pos_attrib as gl::types::GLuint);
-        }
+    pub fn initialize(&mut self, gl: &Rc<gl::Gl>, vtx2xyz: &Vec<f32>) {
+        let mut vbo = Buffer::new(gl, gl::ARRAY_BUFFER);
+        vbo.set_data(vtx2xyz, BufferUsage::Static);
+        let vao = VertexArray::new(gl);
+        vao.bind();
+        VertexLayout::new(3 * std::mem::size_of::<f32>())
+            .with_float("position", 3, 0)
+            .bind(gl, &self.program);
+        self.vao = Some(vao);
+        self.vbo = Some(vbo);
     }
 
     pub fn draw_frame(&self, gl: &gl::Gl, mat_modelview: &[f32], mat_projection: &[f32]) {
+        let mat_modelview: &[f32; 16] = mat_modelview.try_into().unwrap();
         let mp0 = mat_projection;
         let mp1: [f32; 16] = [
             // mp1 = [z flip] * mp0
@@ -89,12 +78,15 @@ void main() {
             -mp0[10], mp0[11], mp0[12], mp0[13], -mp0[14], mp0[15],
         ];
         unsafe {
-            gl.UseProgram(self.program);
-            gl.BindVertexArray(self.vao);
-            gl.Uniform3f(self.loc_color, self.color[0], self.color[1], self.color[2]);
-            gl.UniformMatrix4fv(self.loc_mat_modelview, 1, gl::FALSE, mat_modelview.as_ptr());
-            gl.UniformMatrix4fv(self.loc_mat_projection, 1, gl::FALSE, mp1.as_ptr());
+            gl.UseProgram(self.program.id());
+            if let Some(vao) = &self.vao {
+                vao.bind();
+            }
+            self.program.set_if_active(gl, "color", &self.color);
+            self.program.set_if_active(gl, "matMV", mat_modelview);
+            self.program.set_if_active(gl, "matPrj", &mp1);
             gl.DrawArrays(self.mode, 0, self.elem_size.try_into().unwrap());
         }
+        crate::debug::check_error(gl, "DrawArrays in drawer_array");
     }
 }
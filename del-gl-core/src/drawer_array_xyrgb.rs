@@ -1,22 +1,16 @@
 //! draw array of position and color
 
+use crate::buffer::{Buffer, BufferUsage, VertexArray};
 use crate::gl;
+use crate::shader_program::{ShaderProgram, ShaderSource};
+use crate::vertex_layout::VertexLayout;
+use std::rc::Rc;
 
-pub struct Drawer {
-    pub program: gl::types::GLuint,
-    pub mode: gl::types::GLenum,
-}
-
-impl Drawer {
-    pub fn compile_shader(&mut self, gl: &gl::Gl) {
-        const VS_SRC: &[u8] = b"
-#version 100
-precision mediump float;
+const VS_SRC: &[u8] = b"
+in vec2 position;
+in vec3 color;
 
-attribute vec2 position;
-attribute vec3 color;
-
-varying vec3 v_color;
+out vec3 v_color;
 
 void main() {
     gl_Position = vec4(position, 0.0, 1.0);
@@ -24,66 +18,63 @@ void main() {
 }
 \0";
 
-        const FS_SRC: &[u8] = b"
-#version 100
-precision mediump float;
+const FS_SRC: &[u8] = b"
+in vec3 v_color;
 
-varying vec3 v_color;
+out vec4 FragColor;
 
 void main() {
-    gl_FragColor = vec4(v_color, 1.0);
+    FragColor = vec4(v_color, 1.0);
 }
 \0";
-        unsafe {
-            self.program = crate::utility::compile_shaders(gl, VS_SRC, FS_SRC);
+
+pub struct Drawer {
+    pub program: ShaderProgram,
+    pub mode: gl::types::GLenum,
+    pub vao: Option<VertexArray>,
+    pub vbo: Option<Buffer>,
+}
+
+impl Drawer {
+    pub fn new(mode: gl::types::GLenum) -> Self {
+        Drawer {
+            program: ShaderProgram::new(
+                ShaderSource::from_bytes(VS_SRC),
+                ShaderSource::from_bytes(FS_SRC),
+            ),
+            mode,
+            vao: None,
+            vbo: None,
         }
     }
 
-    pub fn initialize(&self, gl: &gl::Gl, vtx2xyrgb: &Vec<f32>) {
-        let mut vb = 0_u32;
-        unsafe {
-            gl.GenBuffers(1, &mut vb);
-            gl.BindBuffer(gl::ARRAY_BUFFER, vb);
-            gl.BufferData(
-                gl::ARRAY_BUFFER,
-                (vtx2xyrgb.len() * std::mem::size_of::<f32>()) as gl::types::GLsizeiptr,
-                vtx2xyrgb.as_ptr() as *const _,
-                gl::STATIC_DRAW,
-            );
-
-            if gl.BindVertexArray.is_loaded() {
-                let mut vao = std::mem::zeroed();
-                gl.GenVertexArrays(1, &mut vao);
-                gl.BindVertexArray(vao);
-            }
+    pub fn compile_shader(&mut self, gl: &Rc<gl::Gl>) -> Result<(), crate::ShaderError> {
+        self.program.compile(gl)
+    }
 
-            let pos_attrib = gl.GetAttribLocation(self.program, b"position\0".as_ptr() as *const _);
-            let color_attrib = gl.GetAttribLocation(self.program, b"color\0".as_ptr() as *const _);
-            gl.VertexAttribPointer(
-                pos_attrib as gl::types::GLuint,
-                2,
-                gl::FLOAT,
-                0,
-                5 * std::mem::size_of::<f32>() as gl::types::GLsizei,
-                std::ptr::null(),
-            );
-            gl.VertexAttribPointer(
-                color_attrib as gl::types::GLuint,
-                3,
-                gl::FLOAT,
-                0,
-                5 * std::mem::size_of::<f32>() as gl::types::GLsizei,
-                (2 * std::mem::size_of::<f32>()) as *const () as *const _,
-            );
-            gl.EnableVertexAttribArray(pos_attrib as gl::types::GLuint);
-            gl.EnableVertexAttribArray(color_attrib as gl::types::GLuint);
-        }
+    pub fn initialize(&mut self, gl: &Rc<gl::Gl>, vtx2xyrgb: &Vec<f32>) {
+        let mut vbo = Buffer::new(gl, gl::ARRAY_BUFFER);
+        vbo.set_data(vtx2xyrgb, BufferUsage::Static);
+        let vao = VertexArray::new(gl);
+        vao.bind();
+        // interleaved xy and rgb
+        let float_size = std::mem::size_of::<f32>();
+        VertexLayout::new(5 * float_size)
+            .with_float("position", 2, 0)
+            .with_float("color", 3, 2 * float_size)
+            .bind(gl, &self.program);
+        self.vao = Some(vao);
+        self.vbo = Some(vbo);
     }
 
     pub fn draw_frame(&self, gl: &gl::Gl) {
         unsafe {
-            gl.UseProgram(self.program);
+            gl.UseProgram(self.program.id());
+            if let Some(vao) = &self.vao {
+                vao.bind();
+            }
             gl.DrawArrays(self.mode, 0, 3);
         }
+        crate::debug::check_error(gl, "DrawArrays in drawer_array_xyrgb");
     }
 }
@@ -1,8 +1,11 @@
+use crate::buffer::{Buffer, VertexArray};
 use crate::gl;
+use crate::shader_program::{ShaderProgram, ShaderSource};
+use crate::texture::Texture;
+use crate::vertex_layout::VertexLayout;
+use std::rc::Rc;
 
 const VERTEX_SHADER_SOURCE: &[u8] = b"
-#version 330
-
 layout(location=0) in vec2 position;
 layout(location=1) in vec2 texIn;
 out vec2 texPrj;
@@ -14,8 +17,6 @@ void main() {
 \0";
 
 const FRAGMENT_SHADER_SOURCE: &[u8] = b"
-#version 330
-
 in vec2 texPrj;
 out vec4 FragColor;
 uniform sampler2D myTextureSampler;
@@ -27,36 +28,40 @@ void main() {
 \0";
 
 pub struct Drawer {
-    pub gl: gl::Gl,
-    pub program: gl::types::GLuint,
-    pub vao: gl::types::GLuint,
-    pub vbo: gl::types::GLuint,
-    pub loc_tex: gl::types::GLint,
-    pub id_tex: gl::types::GLuint,
+    pub gl: Rc<gl::Gl>,
+    pub program: ShaderProgram,
+    pub vao: VertexArray,
+    pub vbo: Buffer,
+    pub tex: Texture,
 }
 
 impl Drawer {
-    pub fn new(gl0: gl::Gl) -> Self {
+    /// the GL context of `gl0` needs to be current
+    pub fn new(gl0: Rc<gl::Gl>) -> Self {
         Self {
+            program: ShaderProgram::new(
+                ShaderSource::from_bytes(VERTEX_SHADER_SOURCE),
+                ShaderSource::from_bytes(FRAGMENT_SHADER_SOURCE),
+            ),
+            vao: VertexArray::new(&gl0),
+            vbo: Buffer::new(&gl0, gl::ARRAY_BUFFER),
+            tex: Texture::new(&gl0, gl::TEXTURE_2D),
             gl: gl0,
-            program: 0,
-            vao: 0,
-            vbo: 0,
-            loc_tex: 0,
-            id_tex: 0,
         }
     }
 
     pub fn draw(&self) {
         unsafe {
-            self.gl.UseProgram(self.program);
-            self.gl.Uniform1i(self.loc_tex, 0);
-            self.gl.BindVertexArray(self.vao);
-            self.gl.BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            self.gl.UseProgram(self.program.id());
+            self.program
+                .set_if_active(&self.gl, "myTextureSampler", &0i32);
+            self.vao.bind();
+            self.vbo.bind();
             self.gl.ClearColor(0.8, 0.8, 0.8, 0.3);
             self.gl.Clear(gl::COLOR_BUFFER_BIT);
             self.gl.DrawArrays(gl::TRIANGLES, 0, 6);
         }
+        crate::debug::check_error(&self.gl, "DrawArrays in drawer_array_xyzuv");
     }
 
     pub fn resize(&self, width: i32, height: i32) {
@@ -65,54 +70,53 @@ impl Drawer {
         }
     }
 
-    pub fn init_gl(&mut self) {
+    pub fn init_gl(&mut self) -> Result<(), crate::ShaderError> {
         unsafe {
             let gl = &self.gl;
             crate::print_info(gl);
-            self.program =
-                crate::set_shader_program(&gl, VERTEX_SHADER_SOURCE, FRAGMENT_SHADER_SOURCE);
-            let loc_xyz = crate::utility::get_attrib_location(gl, "position", self.program);
-            let loc_uv = crate::utility::get_attrib_location(gl, "texIn", self.program);
-            let loc_tex =
-                crate::utility::get_uniform_location(gl, "myTextureSampler", self.program);
+            self.program.compile(gl)?;
+            gl.UseProgram(self.program.id());
+            let loc_xyz = self.program.attrib_location("position");
+            let loc_uv = self.program.attrib_location("texIn");
+            let loc_tex = self.program.uniform_location("myTextureSampler");
             assert_ne!(loc_xyz, -1);
             assert_ne!(loc_uv, -1);
             assert_ne!(loc_tex, -1);
             //
-            self.vao = std::mem::zeroed();
-            gl.GenVertexArrays(1, &mut self.vao);
-            gl.BindVertexArray(self.vao);
-            self.vbo = std::mem::zeroed();
-            gl.GenBuffers(1, &mut self.vbo);
-            gl.BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            self.vao.bind();
+            self.vbo.bind();
             // gl.BufferData(gl::ARRAY_BUFFER, 0, 0 as *const _, gl::STATIC_DRAW);
-            dbg!(self.program, loc_xyz, loc_uv, loc_tex);
-            gl.VertexAttribPointer(
-                loc_xyz as gl::types::GLuint,
-                2,
-                gl::FLOAT,
-                0,
-                4 * std::mem::size_of::<f32>() as gl::types::GLsizei,
-                std::ptr::null(),
-            );
-            gl.VertexAttribPointer(
-                loc_uv as gl::types::GLuint,
-                2,
-                gl::FLOAT,
-                0,
-                4 * std::mem::size_of::<f32>() as gl::types::GLsizei,
-                (2 * std::mem::size_of::<f32>()) as *const () as *const _,
-            );
-            gl.EnableVertexAttribArray(loc_xyz as gl::types::GLuint);
-            gl.EnableVertexAttribArray(loc_uv as gl::types::GLuint);
+            dbg!(self.program.id(), loc_xyz, loc_uv, loc_tex);
+            // interleaved xy and uv
+            let float_size = std::mem::size_of::<f32>();
+            VertexLayout::new(4 * float_size)
+                .with_float("position", 2, 0)
+                .with_float("texIn", 2, 2 * float_size)
+                .bind(gl, &self.program);
             //
-            gl.Enable(gl::TEXTURE_2D);
             gl.ActiveTexture(gl::TEXTURE0);
-            let mut id_tex: gl::types::GLuint = 0;
-            gl.GenTextures(1, &mut id_tex);
-            gl.BindTexture(gl::TEXTURE_2D, id_tex);
-            self.id_tex = id_tex;
+            self.tex.bind();
         }
+        Ok(())
+    }
+
+    /// same as `init_gl` but the shaders are compiled from files.
+    /// The files are watched by `reload_shader_if_changed`
+    pub fn init_gl_from_files<P: Into<std::path::PathBuf>>(
+        &mut self,
+        path_vertex: P,
+        path_fragment: P,
+    ) -> Result<(), crate::ShaderError> {
+        self.program.set_sources(
+            ShaderSource::from_path(path_vertex),
+            ShaderSource::from_path(path_fragment),
+        );
+        self.init_gl()
+    }
+
+    /// `ShaderProgram::reload_if_changed` with the context of this drawer
+    pub fn reload_shader_if_changed(&mut self) -> Result<bool, crate::ShaderError> {
+        self.program.reload_if_changed(&self.gl)
     }
 }
 
@@ -122,13 +126,3 @@ impl std::ops::Deref for Drawer {
         &self.gl
     }
 }
-
-impl Drop for Drawer {
-    fn drop(&mut self) {
-        unsafe {
-            self.gl.DeleteProgram(self.program);
-            self.gl.DeleteBuffers(1, &self.vbo);
-            self.gl.DeleteVertexArrays(1, &self.vao);
-        }
-    }
-}
@@ -1,144 +1,710 @@
-//! draw mesh position. The RGB color is defined par index
+//! draw mesh position. The RGB color is defined par index, or
+//! per vertex or per element (see `ColorSource`).
+//! The triangles are shaded with the directional lights if the normals are set
+//! or `flat_shading` is enabled. The edges of the triangles can be drawn over the
+//! shading in the same pass with a geometry shader (see `set_wireframe`)
 
+use crate::buffer::{Buffer, BufferUsage, VertexArray};
 use crate::gl;
+use crate::lighting::{DirectionalLight, Material};
+use crate::render_state::{Blend, RenderState, SavedState};
+use crate::shader_program::{ShaderProgram, ShaderSource};
+use crate::texture::{Filter, Texture, TextureDesc, TextureFormat};
+use crate::vertex_layout::VertexLayout;
+use std::rc::Rc;
+
+/// `VTX_NORMAL`, `VTX_POS_VIEW`, `VTX_COLOR`, `POINT_SIZE` and `MAX_LIGHT` are defined
+const VS_SRC: &[u8] = b"
+#include \"mesh_vertex\"
+\0";
+
+/// vertex shader of the wireframe program. The outputs of `mesh_vertex` are renamed to be
+/// passed through the geometry shader. `VTX_NORMAL`, `VTX_POS_VIEW` and `VTX_COLOR` are defined
+const VS_WIRE_SRC: &[u8] = b"
+#define nrm v_nrm
+#define pos_view v_pos_view
+#define clr_vtx v_clr_vtx
+#include \"mesh_vertex\"
+\0";
+
+/// distances from the three edges of the triangle in pixels for the wireframe
+const GS_WIRE_SRC: &[u8] = b"
+layout (triangles) in;
+layout (triangle_strip, max_vertices = 3) out;
+
+uniform vec2 viewport_size;
+in vec3 v_nrm[];
+in vec3 v_pos_view[];
+in vec4 v_clr_vtx[];
+out vec3 nrm;
+out vec3 pos_view;
+out vec4 clr_vtx;
+noperspective out vec3 edge_dist;
+
+void main() {
+    vec2 p[3];
+    for (int i = 0; i < 3; ++i) {
+        p[i] = 0.5 * viewport_size * gl_in[i].gl_Position.xy / gl_in[i].gl_Position.w;
+    }
+    // twice the area of the triangle in pixels
+    vec2 e1 = p[1] - p[0];
+    vec2 e2 = p[2] - p[0];
+    float area = abs(e1.x * e2.y - e1.y * e2.x);
+    for (int i = 0; i < 3; ++i) {
+        // height from the opposite edge. zero at the other two vertices
+        vec3 d = vec3(0.0);
+        d[i] = area / length(p[(i + 2) % 3] - p[(i + 1) % 3]);
+        edge_dist = d;
+        nrm = v_nrm[i];
+        pos_view = v_pos_view[i];
+        clr_vtx = v_clr_vtx[i];
+        // for the colors of the elements
+        gl_PrimitiveID = gl_PrimitiveIDIn;
+        gl_Position = gl_in[i].gl_Position;
+        EmitVertex();
+    }
+    EndPrimitive();
+}
+\0";
+
+/// `WIREFRAME` is defined in the wireframe program
+const FS_SRC: &[u8] = b"
+#include \"lighting\"
+
+uniform vec3 color;
+uniform bool lighting;
+uniform bool two_sided;
+uniform bool flat_shading;
+uniform vec3 ambient;
+uniform vec3 specular;
+uniform float shininess;
+uniform vec3 back_color;
+uniform int num_light;
+uniform vec3 light_dirs[MAX_LIGHT];
+uniform vec3 light_colors[MAX_LIGHT];
+uniform int color_source;
+uniform float alpha;
+
+in vec3 nrm;
+in vec3 pos_view;
+in vec4 clr_vtx;
+
+// `gl_PrimitiveID` is not available in GLSL ES and GLSL 1.20
+#if !defined(GL_ES) && __VERSION__ >= 330
+#define ELEMENT_COLOR
+uniform sampler2D elem_colors;
+#endif
+
+#ifdef WIREFRAME
+uniform vec3 wire_color;
+uniform float wire_width;
+uniform bool wire_hide_back;
+noperspective in vec3 edge_dist;
+#endif
+
+layout (location = 0) out vec4 FragColor;
+
+// weighted blended transparency into the two attachments of `oit::Target`
+#if __VERSION__ >= 300
+#define OIT
+layout (location = 1) out vec4 FragAux;
+uniform bool oit;
+#endif
+
+/// color multiplied to the material. white if `color_source` is zero (uniform)
+vec4 source_color() {
+    if (color_source == 1) {
+        return clr_vtx;
+    }
+#ifdef ELEMENT_COLOR
+    if (color_source == 2) {
+        int w = textureSize(elem_colors, 0).x;
+        return texelFetch(elem_colors, ivec2(gl_PrimitiveID % w, gl_PrimitiveID / w), 0);
+    }
+#endif
+    return vec4(1.0);
+}
+
+vec3 shade(vec3 base) {
+    if (!lighting) {
+        return color * base;
+    }
+    vec3 n = nrm;
+    vec3 diffuse = color * base;
+    if (flat_shading) {
+        // already toward the camera on both sides
+        n = face_normal(pos_view);
+    } else if (two_sided && !gl_FrontFacing) {
+        n = -n;
+    }
+    if (two_sided && !gl_FrontFacing) {
+        diffuse = back_color;
+    }
+    vec3 clr = ambient * base;
+    for (int i = 0; i < MAX_LIGHT; ++i) {
+        if (i < num_light) {
+            clr += light_colors[i] * blinn_phong(n, light_dirs[i], diffuse, specular, shininess);
+        }
+    }
+    return clr;
+}
+
+void main() {
+    vec4 base = source_color();
+    vec3 clr = shade(base.rgb);
+#ifdef WIREFRAME
+    if (!wire_hide_back || gl_FrontFacing) {
+        float d = min(edge_dist.x, min(edge_dist.y, edge_dist.z));
+        // antialiased over a pixel at the border of the line
+        float t = clamp(d - 0.5 * wire_width + 0.5, 0.0, 1.0);
+        clr = mix(wire_color, clr, t);
+    }
+#endif
+    float a = base.a * alpha;
+    FragColor = vec4(clr, a);
+#ifdef OIT
+    FragAux = vec4(0.0);
+    if (oit) {
+        // weight decreasing with the depth (eq. 10 of McGuire and Bavoil)
+        float w = clamp(a * max(1.0e-2, 3.0e3 * pow(1.0 - gl_FragCoord.z, 3.0)), 1.0e-2, 3.0e3);
+        FragColor = vec4(clr * a * w, a);
+        FragAux = vec4(a * w);
+    }
+#endif
+}
+\0";
+
+/// handle of the elements added to the drawer. It stays valid until the elements are removed.
+/// Also used by `drawer_mesh_tex`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ElementId(u32);
+
+/// elements of a drawer in the order added, looked up by their ids.
+/// Also used by `drawer_mesh_tex`
+pub(crate) struct Elements<E> {
+    list: Vec<(ElementId, E)>,
+    next_id: u32,
+}
+
+impl<E> Elements<E> {
+    pub(crate) fn new() -> Self {
+        Elements {
+            list: vec![],
+            next_id: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, elem: E) -> ElementId {
+        let id = ElementId(self.next_id);
+        self.next_id += 1;
+        self.list.push((id, elem));
+        id
+    }
+
+    /// `None` if the elements are removed
+    pub(crate) fn get_mut(&mut self, id: ElementId) -> Option<&mut E> {
+        self.list.iter_mut().find(|(i, _)| *i == id).map(|(_, e)| e)
+    }
+
+    /// `false` if already removed
+    pub(crate) fn remove(&mut self, id: ElementId) -> bool {
+        let num_elem = self.list.len();
+        self.list.retain(|(i, _)| *i != id);
+        self.list.len() != num_elem
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &E> {
+        self.list.iter().map(|(_, e)| e)
+    }
+}
+
+/// source of the color multiplied to the material of the elements.
+/// Use a white material to show the colors as they are
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSource {
+    /// the material only
+    Uniform = 0,
+    /// the colors set by `update_color_per_vertex`
+    Vertex = 1,
+    /// one color per primitive set by `update_color_per_element`.
+    /// Not available in GLSL ES (the color is white) as `gl_PrimitiveID` is needed
+    Element = 2,
+}
+
+/// width of the texture storing the colors of the elements
+const ELEM_COLOR_TEXTURE_WIDTH: usize = 1024;
 
 struct ElementBufferObject {
     mode: gl::types::GLenum,
     elem_size: usize,
-    ebo: gl::types::GLuint,
-    color: [f32; 3],
+    /// `gl::UNSIGNED_INT`, `gl::UNSIGNED_SHORT` or `gl::UNSIGNED_BYTE`
+    index_type: gl::types::GLenum,
+    ebo: Buffer,
+    material: Material,
+    state: RenderState,
+    visible: bool,
+    color_source: ColorSource,
+    /// RGBA8 texture of the colors of the primitives
+    elem_colors: Option<Texture>,
+}
+
+/// the triangles are shaded, and the points and lines are drawn in the flat color
+fn is_triangle(mode: gl::types::GLenum) -> bool {
+    matches!(mode, gl::TRIANGLES | gl::TRIANGLE_STRIP | gl::TRIANGLE_FAN)
+}
+
+/// edges of the triangles drawn over the shading
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wireframe {
+    pub color: [f32; 3],
+    /// width of the lines in pixels
+    pub width: f32,
+    /// do not draw the edges on the back faces
+    pub hide_back: bool,
+}
+
+impl Wireframe {
+    /// lines of one pixel width
+    pub fn new(color: [f32; 3]) -> Self {
+        Wireframe {
+            color,
+            width: 1.0,
+            hide_back: false,
+        }
+    }
+
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn hide_back(mut self, hide_back: bool) -> Self {
+        self.hide_back = hide_back;
+        self
+    }
 }
 
 pub struct Drawer {
-    program: gl::types::GLuint,
+    program: ShaderProgram,
+    /// program with the geometry shader for the triangles with the wireframe.
+    /// compiled when the wireframe is set for the first time
+    program_wire: ShaderProgram,
+    wireframe: Option<Wireframe>,
     pub ndim: i32,
     num_point: i32,
-    vao: gl::types::GLuint,
-    // uniform variables
-    loc_color: gl::types::GLint,
-    loc_mat_modelview: gl::types::GLint,
-    loc_mat_projection: gl::types::GLint,
+    /// usage hint of the vertex buffers (see [`BufferUsage`])
+    pub usage: BufferUsage,
+    vao: Option<VertexArray>,
+    vbo: Option<Buffer>,
+    /// normal buffer. The lighting is enabled once the normals are set
+    nbo: Option<Buffer>,
+    /// color buffer of the vertices
+    cbo: Option<Buffer>,
+    /// the headlight by default. At most `lighting::MAX_LIGHT` lights are used
+    pub lights: Vec<DirectionalLight>,
+    /// light the back faces with the `back` color of the material
+    pub two_sided: bool,
+    /// shade the triangles with the face normals computed in the fragment shader.
+    /// The lighting is enabled without setting the normals
+    pub flat_shading: bool,
     // elemenb buffer object
-    ebos: Vec<ElementBufferObject>,
+    ebos: Elements<ElementBufferObject>,
 }
 
 impl Drawer {
     pub fn new() -> Self {
+        let mut program = ShaderProgram::new(
+            ShaderSource::from_bytes(VS_SRC),
+            ShaderSource::from_bytes(FS_SRC),
+        );
+        program.preprocessor.define("VTX_NORMAL", 1);
+        program.preprocessor.define("VTX_POS_VIEW", 1);
+        program.preprocessor.define("VTX_COLOR", 1);
+        program.preprocessor.define("POINT_SIZE", 1);
+        program
+            .preprocessor
+            .define("MAX_LIGHT", crate::lighting::MAX_LIGHT);
+        let mut program_wire = ShaderProgram::new(
+            ShaderSource::from_bytes(VS_WIRE_SRC),
+            ShaderSource::from_bytes(FS_SRC),
+        )
+        .with_geometry(ShaderSource::from_bytes(GS_WIRE_SRC));
+        program_wire.preprocessor.define("VTX_NORMAL", 1);
+        program_wire.preprocessor.define("VTX_POS_VIEW", 1);
+        program_wire.preprocessor.define("VTX_COLOR", 1);
+        program_wire.preprocessor.define("WIREFRAME", 1);
+        program_wire
+            .preprocessor
+            .define("MAX_LIGHT", crate::lighting::MAX_LIGHT);
         Drawer {
-            program: 0,
+            program,
+            program_wire,
+            wireframe: None,
             ndim: 0,
             num_point: 0,
-            vao: 0,
-            loc_color: -1, // -1 is the failure flag
-            loc_mat_modelview: -1,
-            loc_mat_projection: -1,
-            ebos: Vec::<ElementBufferObject>::new(),
+            usage: BufferUsage::Static,
+            vao: None,
+            vbo: None,
+            nbo: None,
+            cbo: None,
+            lights: vec![DirectionalLight::headlight()],
+            two_sided: true,
+            flat_shading: false,
+            ebos: Elements::new(),
         }
     }
-    pub fn compile_shader(&mut self, gl: &gl::Gl) {
-        const VS_SRC: &[u8] = b"
-#version 330
-
-uniform mat4 matMV;
-uniform mat4 matPrj;
-
-layout (location = 0) in vec3 position;
+    pub fn compile_shader(&mut self, gl: &Rc<gl::Gl>) -> Result<(), crate::ShaderError> {
+        self.program.compile(gl)?;
+        if self.vao.is_none() {
+            self.vao = Some(VertexArray::new(gl));
+        }
+        Ok(())
+    }
 
-void main() {
-    gl_Position = matPrj * matMV * vec4(position, 1.0);
-    // gl_Position = vec4(position, 1.0);
-}
-\0";
+    /// compile the shaders from files instead of the embedded sources.
+    /// The files are watched by `reload_shader_if_changed`
+    pub fn compile_shader_from_files<P: Into<std::path::PathBuf>>(
+        &mut self,
+        gl: &Rc<gl::Gl>,
+        path_vertex: P,
+        path_fragment: P,
+    ) -> Result<(), crate::ShaderError> {
+        self.program.set_sources(
+            ShaderSource::from_path(path_vertex),
+            ShaderSource::from_path(path_fragment),
+        );
+        self.compile_shader(gl)
+    }
 
-        const FS_SRC: &[u8] = b"
-#version 330
+    /// compile the shaders of the wireframe program from files instead of the embedded sources.
+    /// The files are watched by `reload_shader_if_changed`. The vertex shader needs to take
+    /// the attributes at the same locations as the main program (e.g., with `mesh_vertex`)
+    pub fn compile_wireframe_shader_from_files<P: Into<std::path::PathBuf>>(
+        &mut self,
+        gl: &Rc<gl::Gl>,
+        path_vertex: P,
+        path_geometry: P,
+        path_fragment: P,
+    ) -> Result<(), crate::ShaderError> {
+        self.program_wire.set_sources(
+            ShaderSource::from_path(path_vertex),
+            ShaderSource::from_path(path_fragment),
+        );
+        self.program_wire
+            .set_geometry(Some(ShaderSource::from_path(path_geometry)));
+        self.compile_wireframe_shader(gl)
+    }
 
-uniform vec3 color;
+    /// compile the wireframe program. It shares the vertex array with the main program
+    fn compile_wireframe_shader(&mut self, gl: &Rc<gl::Gl>) -> Result<(), crate::ShaderError> {
+        self.program_wire.dialect = self.program.dialect;
+        self.program_wire.compile(gl)?;
+        self.check_wireframe_attributes()
+    }
 
-out vec4 FragColor;
+    /// the wireframe program is deleted if its attributes are not at the locations
+    /// bound to the vertex array for the main program
+    fn check_wireframe_attributes(&mut self) -> Result<(), crate::ShaderError> {
+        if self.program.id() == 0 {
+            return Ok(());
+        }
+        for attr in self.program_wire.attributes() {
+            let location = self.program.attrib_location(&attr.name);
+            if location != -1 && location != attr.location {
+                let log = format!(
+                    "attribute `{}` is at location {} in the wireframe program but at {} in the main program",
+                    attr.name, attr.location, location
+                );
+                self.program_wire.delete();
+                return Err(crate::ShaderError {
+                    stage: crate::ShaderStage::Link,
+                    log,
+                    annotated_source: String::new(),
+                });
+            }
+        }
+        Ok(())
+    }
 
-void main() {
-    FragColor = vec4(color, 1.0);
-}
-\0";
+    /// `ShaderProgram::reload_if_changed` of the main program, and of the wireframe
+    /// program once it is compiled
+    pub fn reload_shader_if_changed(
+        &mut self,
+        gl: &Rc<gl::Gl>,
+    ) -> Result<bool, crate::ShaderError> {
+        let mut reloaded = self.program.reload_if_changed(gl)?;
+        if self.program_wire.id() != 0 && self.program_wire.reload_if_changed(gl)? {
+            reloaded = true;
+            self.check_wireframe_attributes()?;
+        }
+        Ok(reloaded)
+    }
 
-        use crate::utility::{compile_shaders, get_uniform_location};
-        unsafe {
-            self.program = compile_shaders(gl, VS_SRC, FS_SRC);
-            self.loc_mat_modelview = get_uniform_location(gl, "matMV", self.program);
-            self.loc_mat_projection = get_uniform_location(gl, "matPrj", self.program);
-            self.loc_color = get_uniform_location(gl, "color", self.program);
-            if gl.BindVertexArray.is_loaded() {
-                let mut vao0 = std::mem::zeroed();
-                gl.GenVertexArrays(1, &mut vao0);
-                self.vao = vao0;
-                gl.BindVertexArray(self.vao);
-            }
+    /// draw the edges of the triangles over the shading in the same pass, or stop drawing
+    /// them with `None`. This fails unless the context supports the geometry shader
+    /// (the `Glsl330` dialect). The wireframe program is built from the embedded sources
+    /// unless `compile_wireframe_shader_from_files` is called
+    pub fn set_wireframe(
+        &mut self,
+        gl: &Rc<gl::Gl>,
+        wireframe: Option<Wireframe>,
+    ) -> Result<(), crate::ShaderError> {
+        if wireframe.is_some() && self.program_wire.id() == 0 {
+            self.compile_wireframe_shader(gl)?;
         }
+        self.wireframe = wireframe;
+        Ok(())
+    }
+
+    /// change the color or width of the wireframe without recompiling
+    pub fn wireframe_mut(&mut self) -> Option<&mut Wireframe> {
+        self.wireframe.as_mut()
     }
 
     pub fn add_element<T>(
         &mut self,
-        gl: &gl::Gl,
+        gl: &Rc<gl::Gl>,
         mode: gl::types::GLenum,
         elem2vtx: &Vec<T>,
         color: [f32; 3],
-    ) where
+    ) -> ElementId
+    where
         T: 'static + Copy + num_traits::AsPrimitive<gl::types::GLuint>,
     {
-        use crate::gl::types::GLuint;
-        let elem2vtx0: Vec<GLuint> = elem2vtx.iter().map(|i| (*i).as_()).collect();
-        unsafe {
-            gl.BindVertexArray(self.vao);
-            let mut ebo0 = 0_u32;
-            gl.GenBuffers(1, &mut ebo0);
-            gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo0);
-            // println!("{:?}", (elem2vtx0.len() * std::mem::size_of::<usize>()) as gl::types::GLsizeiptr);
-            // println!("{:?}", elem2vtx0.as_ptr() as *const _);
-            gl.BufferData(
-                gl::ELEMENT_ARRAY_BUFFER,
-                (elem2vtx0.len() * std::mem::size_of::<GLuint>()) as gl::types::GLsizeiptr,
-                elem2vtx0.as_ptr() as *const _,
-                gl::STATIC_DRAW,
-            );
-            self.ebos.push(ElementBufferObject {
-                mode,
-                elem_size: elem2vtx0.len(),
-                ebo: ebo0,
-                color,
-            });
-        }
-    }
-
-    pub fn update_vertex(&mut self, gl: &gl::Gl, vtx_xyz: &Vec<f32>, ndim: i32) {
+        self.add_element_with_material(gl, mode, elem2vtx, Material::new(color))
+    }
+
+    /// add the elements drawn with `material`. Only the `diffuse` color is used
+    /// for the points and lines, or if the normals are not set.
+    /// The elements are drawn in the order added
+    pub fn add_element_with_material<T>(
+        &mut self,
+        gl: &Rc<gl::Gl>,
+        mode: gl::types::GLenum,
+        elem2vtx: &[T],
+        material: Material,
+    ) -> ElementId
+    where
+        T: 'static + Copy + num_traits::AsPrimitive<gl::types::GLuint>,
+    {
+        self.add_element_with_state(gl, mode, elem2vtx, material, RenderState::default())
+    }
+
+    /// add the elements drawn with `material` in the render `state`
+    pub fn add_element_with_state<T>(
+        &mut self,
+        gl: &Rc<gl::Gl>,
+        mode: gl::types::GLenum,
+        elem2vtx: &[T],
+        material: Material,
+        state: RenderState,
+    ) -> ElementId
+    where
+        T: 'static + Copy + num_traits::AsPrimitive<gl::types::GLuint>,
+    {
+        if let Some(vao) = &self.vao {
+            vao.bind();
+        }
+        let mut ebo = Buffer::new(gl, gl::ELEMENT_ARRAY_BUFFER);
+        let index_type = ebo.set_indices(elem2vtx, BufferUsage::Static);
+        ebo.set_label("drawer_mesh element");
+        self.ebos.push(ElementBufferObject {
+            mode,
+            elem_size: elem2vtx.len(),
+            index_type,
+            ebo,
+            material,
+            state,
+            visible: true,
+            color_source: ColorSource::Uniform,
+            elem_colors: None,
+        })
+    }
+
+    /// `None` if the elements are removed
+    pub fn material_mut(&mut self, id: ElementId) -> Option<&mut Material> {
+        self.ebos.get_mut(id).map(|e| &mut e.material)
+    }
+
+    /// `None` if the elements are removed
+    pub fn render_state_mut(&mut self, id: ElementId) -> Option<&mut RenderState> {
+        self.ebos.get_mut(id).map(|e| &mut e.state)
+    }
+
+    /// change only the diffuse color. The other fields of the material (e.g., the ambient
+    /// and the alpha) are kept. Returns `false` if the elements are removed
+    pub fn set_color(&mut self, id: ElementId, color: [f32; 3]) -> bool {
+        self.material_mut(id).map(|m| m.diffuse = color).is_some()
+    }
+
+    /// the hidden elements keep their buffer and are skipped in `draw`.
+    /// Returns `false` if the elements are removed
+    pub fn set_visible(&mut self, id: ElementId, visible: bool) -> bool {
+        self.ebos.get_mut(id).map(|e| e.visible = visible).is_some()
+    }
+
+    /// returns `false` if the elements are removed
+    pub fn set_color_source(&mut self, id: ElementId, color_source: ColorSource) -> bool {
+        self.ebos
+            .get_mut(id)
+            .map(|e| e.color_source = color_source)
+            .is_some()
+    }
+
+    /// set the colors of the primitives (triangles, lines or points) of the elements
+    /// (`ndim` is 3 for RGB or 4 for RGBA) and switch them to `ColorSource::Element`.
+    /// Returns `false` if the elements are removed
+    pub fn update_color_per_element(
+        &mut self,
+        gl: &Rc<gl::Gl>,
+        id: ElementId,
+        elem2color: &[f32],
+        ndim: usize,
+    ) -> bool {
+        assert!(ndim == 3 || ndim == 4);
+        let Some(elem) = self.ebos.get_mut(id) else {
+            return false;
+        };
+        let num_elem = elem2color.len() / ndim;
+        let width = num_elem.clamp(1, ELEM_COLOR_TEXTURE_WIDTH);
+        let height = num_elem.div_ceil(width).max(1);
+        let mut pix2rgba = vec![255u8; width * height * 4];
+        for (rgba, color) in pix2rgba
+            .chunks_exact_mut(4)
+            .zip(elem2color.chunks_exact(ndim))
+        {
+            for (c, &v) in rgba.iter_mut().zip(color) {
+                *c = (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        }
+        let desc = TextureDesc::new(width, height, TextureFormat::Rgba8)
+            .filter(Filter::Nearest, Filter::Nearest);
+        match &mut elem.elem_colors {
+            Some(tex) if tex.desc() == Some(&desc) => {
+                tex.set_sub_image(0, 0, width, height, &pix2rgba);
+            }
+            _ => {
+                let tex = Texture::from_desc(gl, &desc, Some(&pix2rgba));
+                tex.set_label("drawer_mesh element color");
+                elem.elem_colors = Some(tex);
+            }
+        }
+        elem.color_source = ColorSource::Element;
+        true
+    }
+
+    /// replace the indices of the elements. The buffer is reused if the size is the same.
+    /// Returns `false` if the elements are removed
+    pub fn update_indices<T>(&mut self, id: ElementId, elem2vtx: &[T]) -> bool
+    where
+        T: 'static + Copy + num_traits::AsPrimitive<gl::types::GLuint>,
+    {
+        let Some(elem) = self.ebos.get_mut(id) else {
+            return false;
+        };
+        if let Some(vao) = &self.vao {
+            vao.bind();
+        }
+        elem.index_type = elem.ebo.set_indices(elem2vtx, BufferUsage::Static);
+        elem.elem_size = elem2vtx.len();
+        true
+    }
+
+    /// remove the elements and delete the buffer. Returns `false` if already removed
+    pub fn remove_element(&mut self, id: ElementId) -> bool {
+        self.ebos.remove(id)
+    }
+
+    pub fn update_vertex(&mut self, gl: &Rc<gl::Gl>, vtx_xyz: &Vec<f32>, ndim: i32) {
         self.ndim = ndim;
         self.num_point = vtx_xyz.len() as i32 / self.ndim;
-        unsafe {
-            gl.BindVertexArray(self.vao);
-            let mut vbo = std::mem::zeroed();
-            gl.GenBuffers(1, &mut vbo);
-            gl.BindBuffer(gl::ARRAY_BUFFER, vbo);
-            gl.BufferData(
-                gl::ARRAY_BUFFER,
-                (vtx_xyz.len() * std::mem::size_of::<f32>()) as gl::types::GLsizeiptr,
-                vtx_xyz.as_ptr() as *const _,
-                gl::STATIC_DRAW,
-            );
-            //
-            let pos_attrib = gl.GetAttribLocation(self.program, b"position\0".as_ptr() as *const _);
-            gl.EnableVertexAttribArray(pos_attrib as gl::types::GLuint);
-            gl.VertexAttribPointer(
-                pos_attrib as gl::types::GLuint,
-                self.ndim,
-                gl::FLOAT,
-                0,
-                self.ndim * std::mem::size_of::<f32>() as gl::types::GLsizei,
-                std::ptr::null(),
-            );
+        if let Some(vao) = &self.vao {
+            vao.bind();
         }
+        let vbo = self
+            .vbo
+            .get_or_insert_with(|| Buffer::new(gl, gl::ARRAY_BUFFER));
+        vbo.set_data(vtx_xyz, self.usage);
+        vbo.set_label("drawer_mesh vertex");
+        if let Some(vao) = &self.vao {
+            vao.set_label("drawer_mesh");
+        }
+        let float_size = std::mem::size_of::<f32>();
+        VertexLayout::new(self.ndim as usize * float_size)
+            .with_float("position", self.ndim, 0)
+            .bind(gl, &self.program);
     }
 
+    /// set the normals of the vertices (three floats per vertex) and enable the lighting
+    pub fn update_normal(&mut self, gl: &Rc<gl::Gl>, vtx2nrm: &[f32]) {
+        assert_eq!(vtx2nrm.len(), self.num_point as usize * 3);
+        if let Some(vao) = &self.vao {
+            vao.bind();
+        }
+        let nbo = self
+            .nbo
+            .get_or_insert_with(|| Buffer::new(gl, gl::ARRAY_BUFFER));
+        nbo.set_data(vtx2nrm, self.usage);
+        nbo.set_label("drawer_mesh normal");
+        VertexLayout::new(3 * std::mem::size_of::<f32>())
+            .with_float("normal", 3, 0)
+            .bind(gl, &self.program);
+    }
+
+    /// set the colors of the vertices (`ndim` is 3 for RGB or 4 for RGBA).
+    /// They are used by the elements of `ColorSource::Vertex`
+    pub fn update_color_per_vertex(&mut self, gl: &Rc<gl::Gl>, vtx2color: &[f32], ndim: i32) {
+        assert!(ndim == 3 || ndim == 4);
+        assert_eq!(vtx2color.len(), (self.num_point * ndim) as usize);
+        if let Some(vao) = &self.vao {
+            vao.bind();
+        }
+        let cbo = self
+            .cbo
+            .get_or_insert_with(|| Buffer::new(gl, gl::ARRAY_BUFFER));
+        cbo.set_data(vtx2color, self.usage);
+        cbo.set_label("drawer_mesh color");
+        VertexLayout::new(ndim as usize * std::mem::size_of::<f32>())
+            .with_float("vtx_color", ndim, 0)
+            .bind(gl, &self.program);
+    }
+
+    /// set the area-weighted normals of the triangle mesh `tri2vtx` in 3D
+    pub fn update_normal_from_triangles<T>(
+        &mut self,
+        gl: &Rc<gl::Gl>,
+        tri2vtx: &[T],
+        vtx2xyz: &[f32],
+    ) where
+        T: 'static + Copy + num_traits::AsPrimitive<usize>,
+    {
+        let vtx2nrm = crate::lighting::vtx2normal_from_triangles(tri2vtx, vtx2xyz);
+        self.update_normal(gl, &vtx2nrm);
+    }
+
+    /// draw the elements other than those blended with `Blend::WeightedOit`
     pub fn draw(&self, gl: &gl::Gl, mat_modelview: &[f32; 16], mat_projection: &[f32; 16]) {
+        self.draw_elements(gl, mat_modelview, mat_projection, false);
+    }
+
+    /// draw only the elements blended with `Blend::WeightedOit`. Call this between
+    /// `oit::Target::begin` and `oit::Target::end` after drawing the opaque scene
+    pub fn draw_transparent(
+        &self,
+        gl: &gl::Gl,
+        mat_modelview: &[f32; 16],
+        mat_projection: &[f32; 16],
+    ) {
+        self.draw_elements(gl, mat_modelview, mat_projection, true);
+    }
+
+    fn draw_elements(
+        &self,
+        gl: &gl::Gl,
+        mat_modelview: &[f32; 16],
+        mat_projection: &[f32; 16],
+        oit: bool,
+    ) {
         let mp1 = mat_projection;
         /*
         let mp1: [f32; 16] = [
@@ -147,25 +713,86 @@ void main() {
             -mp0[10], mp0[11], mp0[12], mp0[13], -mp0[14], mp0[15],
         ];
          */
+        let wire = self.wireframe.is_some() && self.program_wire.id() != 0;
+        let is_es = self.program.compiled_dialect().is_some_and(|d| d.is_es());
+        let saved = SavedState::query(gl, is_es);
+        let mut state = saved.state;
         unsafe {
-            gl.UseProgram(self.program);
-            gl.BindVertexArray(self.vao);
-            for ebo in &self.ebos {
-                gl.Uniform3f(self.loc_color, ebo.color[0], ebo.color[1], ebo.color[2]);
-                gl.UniformMatrix4fv(self.loc_mat_modelview, 1, gl::FALSE, mat_modelview.as_ptr());
-                gl.UniformMatrix4fv(self.loc_mat_projection, 1, gl::FALSE, mp1.as_ptr());
-                gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo.ebo);
+            if let Some(vao) = &self.vao {
+                vao.bind();
+            }
+            let mut id_in_use = 0;
+            let ebos = self
+                .ebos
+                .iter()
+                .filter(|e| e.visible && (e.state.blend == Some(Blend::WeightedOit)) == oit);
+            for ebo in ebos {
+                let ebo_state = ebo.state.effective();
+                ebo_state.transit(gl, &state, is_es);
+                state = ebo_state;
+                let program = if wire && is_triangle(ebo.mode) {
+                    &self.program_wire
+                } else {
+                    &self.program
+                };
+                if program.id() != id_in_use {
+                    id_in_use = program.id();
+                    gl.UseProgram(id_in_use);
+                    self.set_common_uniforms(gl, program, mat_modelview, mp1);
+                    program.set_if_active(gl, "oit", &oit);
+                }
+                let lighting = (self.nbo.is_some() || self.flat_shading) && is_triangle(ebo.mode);
+                program.set_if_active(gl, "lighting", &lighting);
+                crate::lighting::set_material(gl, program, &ebo.material);
+                program.set_if_active(gl, "point_size", &ebo.state.point_size);
+                program.set_if_active(gl, "color_source", &(ebo.color_source as i32));
+                if let (ColorSource::Element, Some(tex)) = (ebo.color_source, &ebo.elem_colors) {
+                    gl.ActiveTexture(gl::TEXTURE0);
+                    tex.bind();
+                    program.set_if_active(gl, "elem_colors", &0i32);
+                }
+                ebo.ebo.bind();
                 gl.DrawElements(
                     ebo.mode,
                     ebo.elem_size as i32,
-                    gl::UNSIGNED_INT,
+                    ebo.index_type,
                     std::ptr::null(),
                 );
             }
         }
+        saved.restore(gl, &state, is_es);
+        crate::debug::check_error(gl, "DrawElements in drawer_mesh");
+    }
+
+    /// set the uniforms other than the material. The program needs to be in use
+    fn set_common_uniforms(
+        &self,
+        gl: &gl::Gl,
+        program: &ShaderProgram,
+        mat_modelview: &[f32; 16],
+        mat_projection: &[f32; 16],
+    ) {
+        program.set_if_active(gl, "matMV", mat_modelview);
+        program.set_if_active(gl, "matPrj", mat_projection);
+        program.set_if_active(gl, "two_sided", &self.two_sided);
+        program.set_if_active(gl, "flat_shading", &self.flat_shading);
+        crate::lighting::set_lights(gl, program, &self.lights, mat_modelview);
+        let is_wire = std::ptr::eq(program, &self.program_wire);
+        if let Some(wireframe) = self.wireframe.as_ref().filter(|_| is_wire) {
+            let mut viewport = [0 as gl::types::GLint; 4];
+            unsafe {
+                gl.GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            }
+            let viewport_size = [viewport[2] as f32, viewport[3] as f32];
+            program.set_if_active(gl, "viewport_size", &viewport_size);
+            program.set_if_active(gl, "wire_color", &wireframe.color);
+            program.set_if_active(gl, "wire_width", &wireframe.width);
+            program.set_if_active(gl, "wire_hide_back", &wireframe.hide_back);
+        }
     }
 
     pub fn draw_points(&self, gl: &gl::Gl, mat_modelview: &[f32], mat_projection: &[f32]) {
+        let mat_modelview: &[f32; 16] = mat_modelview.try_into().unwrap();
         let mp0 = mat_projection;
         let mp1: [f32; 16] = [
             // mp1 = [z flip] * mp0
@@ -173,12 +800,18 @@ void main() {
             -mp0[10], mp0[11], mp0[12], mp0[13], -mp0[14], mp0[15],
         ];
         unsafe {
-            gl.UseProgram(self.program);
-            gl.BindVertexArray(self.vao);
-            gl.Uniform3f(self.loc_color, 0., 0., 0.);
-            gl.UniformMatrix4fv(self.loc_mat_modelview, 1, gl::FALSE, mat_modelview.as_ptr());
-            gl.UniformMatrix4fv(self.loc_mat_projection, 1, gl::FALSE, mp1.as_ptr());
+            gl.UseProgram(self.program.id());
+            if let Some(vao) = &self.vao {
+                vao.bind();
+            }
+            self.program.set_if_active(gl, "color", &[0f32, 0., 0.]);
+            self.program.set_if_active(gl, "lighting", &false);
+            self.program.set_if_active(gl, "point_size", &1f32);
+            self.program.set_if_active(gl, "color_source", &0i32);
+            self.program.set_if_active(gl, "matMV", mat_modelview);
+            self.program.set_if_active(gl, "matPrj", &mp1);
             gl.DrawArrays(gl::POINTS, 0, self.num_point);
         }
+        crate::debug::check_error(gl, "DrawArrays in drawer_mesh");
     }
 }
@@ -1,242 +1,207 @@
 //! draw mesh with colormap
 
+use crate::buffer::{Buffer, BufferUsage, VertexArray};
 use crate::gl;
+use crate::shader_program::{ShaderProgram, ShaderSource};
+use crate::vertex_layout::VertexLayout;
+use std::rc::Rc;
+
+/// `VTX_VALUE`, `VTX_POS_VIEW` and `Z_FLIP` are defined
+const VS_SRC: &[u8] = b"
+#include \"mesh_vertex\"
+\0";
+
+/// `NCOLOR` is defined as the number of colors in the color table
+const FS_SRC: &[u8] = b"
+#include \"colormap\"
+#include \"lighting\"
+
+uniform float val_min;
+uniform float val_max;
+uniform bool flat_shading;
+in float val;
+in vec3 pos_view;
+
+out vec4 FragColor;
+
+void main() {
+    vec3 clr = colormap(val, val_min, val_max);
+    if (flat_shading) {
+        // headlight
+        vec3 n = face_normal(pos_view);
+        clr *= 0.3 + 0.7 * max(n.z, 0.0);
+    }
+    FragColor = vec4(clr, 1.0);
+}
+\0";
 
 struct ElementBufferObject {
     mode: gl::types::GLenum,
     elem_size: usize,
-    ebo: gl::types::GLuint,
+    /// `gl::UNSIGNED_INT`, `gl::UNSIGNED_SHORT` or `gl::UNSIGNED_BYTE`
+    index_type: gl::types::GLenum,
+    ebo: Buffer,
 }
 
 pub struct Drawer {
+    /// colors from `val_min` to `val_max`. Viridis by default (see `crate::colormap`).
+    /// The colors are resampled in `draw` if the number differs from the compiled shader
     pub color_map: Vec<[f32; 3]>,
     pub val_min: f32,
     pub val_max: f32,
+    /// shade the faces with a headlight to see the individual triangles
+    pub flat_shading: bool,
     pub ndim: i32,
-    program: gl::types::GLuint,
-    vao: gl::types::GLuint,
-    // uniform variables
-    loc_mat_modelview: gl::types::GLint,
-    loc_mat_projection: gl::types::GLint,
-    loc_val_min: gl::types::GLint,
-    loc_val_max: gl::types::GLint,
+    program: ShaderProgram,
+    /// `NCOLOR` of the compiled shader
+    ncolor: usize,
+    /// usage hint of the vertex buffers (see [`BufferUsage`])
+    pub usage: BufferUsage,
+    vao: Option<VertexArray>,
+    vbo_xyz: Option<Buffer>,
+    vbo_val: Option<Buffer>,
     // elemenb buffer object
-    ebo: ElementBufferObject,
+    ebo: Option<ElementBufferObject>,
 }
 
 impl Drawer {
     pub fn new() -> Self {
+        let mut program = ShaderProgram::new(
+            ShaderSource::from_bytes(VS_SRC),
+            ShaderSource::from_bytes(FS_SRC),
+        );
+        program.preprocessor.define("VTX_VALUE", 1);
+        program.preprocessor.define("VTX_POS_VIEW", 1);
+        program.preprocessor.define("Z_FLIP", 1);
         Drawer {
-            color_map: Vec::<[f32; 3]>::new(),
+            color_map: crate::colormap::Preset::Viridis.colors(),
             val_min: 0.0,
             val_max: 1.0,
-            program: 0,
+            flat_shading: false,
+            program,
+            ncolor: 0,
             ndim: 0,
-            vao: 0,
-            loc_mat_modelview: -1,
-            loc_mat_projection: -1,
-            loc_val_min: -1,
-            loc_val_max: -1,
-            ebo: ElementBufferObject {
-                mode: gl::TRIANGLES,
-                elem_size: 0,
-                ebo: 0,
-            },
+            usage: BufferUsage::Static,
+            vao: None,
+            vbo_xyz: None,
+            vbo_val: None,
+            ebo: None,
         }
     }
 
-    pub fn compile_shader(&mut self, gl: &gl::Gl) {
-        const VS_SRC: &[u8] = b"
-#version 330
-
-uniform mat4 matMV;
-uniform mat4 matPrj;
-
-layout (location = 0) in vec3 position;
-layout (location = 1) in float value;
-out float val;
-
-void main() {
-    gl_Position = matPrj * matMV * vec4(position, 1.0);
-    // gl_Position = vec4(position, 1.0);
-    val = value;
-}
-\0";
-
-        let mut glsl_colormap = String::new();
-        {
-            glsl_colormap += &format!("const int ncolor = {};\n", self.color_map.len());
-            glsl_colormap += "vec3[ncolor] colors = vec3[] (\n";
-            for ic in 0..self.color_map.len() {
-                let c = self.color_map[ic];
-                glsl_colormap += &format!(" vec3({},{},{})", c[0], c[1], c[2]);
-                if ic != self.color_map.len() - 1 {
-                    glsl_colormap += &",\n";
-                } else {
-                    glsl_colormap += &");\n";
-                }
-            }
+    /// compile the shader. The size of the color table is fixed in the fragment shader
+    /// to the number of the colors in `color_map`. The presets of `crate::colormap`
+    /// have the same number of colors, so they are switched without recompiling.
+    /// Recompile it to change the number of the colors
+    pub fn compile_shader(&mut self, gl: &Rc<gl::Gl>) -> Result<(), crate::ShaderError> {
+        let ncolor = self.color_map.len().max(2);
+        self.program.preprocessor.define("NCOLOR", ncolor);
+        self.program.compile(gl)?;
+        self.ncolor = ncolor;
+        if self.vao.is_none() {
+            self.vao = Some(VertexArray::new(gl));
         }
+        Ok(())
+    }
 
-        let glsl_header: String = "
-#version 330
-"
-        .to_string();
-
-        let glsl_code: String = "
-uniform vec3 color;
-
-uniform float val_min;
-uniform float val_max;
-in float val;
-
-out vec4 FragColor;
-
-void main() {
-    float scaled_value = (val-val_min)/(val_max-val_min) * (ncolor-1);
-    int idx_color = int(scaled_value);
-    float r01 = scaled_value - float(idx_color);
-    if( idx_color < 0 ){ idx_color = 0; r01 = 0.;}
-    if( idx_color > ncolor-2 ){ idx_color = ncolor-2; r01 = 1.; }
-    vec3 clr01 = (1.f-r01)*colors[idx_color] + r01*colors[idx_color+1];
-    FragColor = vec4(clr01.x, clr01.y, clr01.z, 1.0f);
-    // FragColor = vec4(color, 1.0);
-}
-\0"
-        .to_string();
-        let fs_src = glsl_header + &glsl_colormap + &glsl_code;
-
-        use crate::utility::{compile_shaders, get_uniform_location};
-        unsafe {
-            self.program = compile_shaders(gl, VS_SRC, fs_src.as_bytes());
-            self.loc_mat_modelview = get_uniform_location(gl, "matMV", self.program);
-            self.loc_mat_projection = get_uniform_location(gl, "matPrj", self.program);
-            self.loc_val_min = get_uniform_location(gl, "val_min", self.program);
-            self.loc_val_max = get_uniform_location(gl, "val_max", self.program);
-
-            // make VAO
-            if gl.BindVertexArray.is_loaded() {
-                let mut vao0 = std::mem::zeroed();
-                gl.GenVertexArrays(1, &mut vao0);
-                self.vao = vao0;
-                gl.BindVertexArray(self.vao);
-            }
+    /// compile the shaders from files instead of the embedded sources.
+    /// The color table is available in the fragment shader with `#include "colormap"`.
+    /// The files are watched by `ShaderProgram::reload_if_changed` (see `program_mut`)
+    pub fn compile_shader_from_files<P: Into<std::path::PathBuf>>(
+        &mut self,
+        gl: &Rc<gl::Gl>,
+        path_vertex: P,
+        path_fragment: P,
+    ) -> Result<(), crate::ShaderError> {
+        self.program.set_sources(
+            ShaderSource::from_path(path_vertex),
+            ShaderSource::from_path(path_fragment),
+        );
+        self.compile_shader(gl)
+    }
 
-            {
-                let cname = std::ffi::CString::new("matMV").expect("CString::new failed");
-                self.loc_mat_modelview = gl.GetUniformLocation(self.program, cname.as_ptr());
-            }
-            {
-                let cname = std::ffi::CString::new("matPrj").expect("CString::new failed");
-                self.loc_mat_projection = gl.GetUniformLocation(self.program, cname.as_ptr());
-            }
-            {
-                let cname = std::ffi::CString::new("val_min").expect("CString::new failed");
-                self.loc_val_min = gl.GetUniformLocation(self.program, cname.as_ptr());
-            }
-            {
-                let cname = std::ffi::CString::new("val_max").expect("CString::new failed");
-                self.loc_val_max = gl.GetUniformLocation(self.program, cname.as_ptr());
-            }
-        }
+    /// program of the drawer, e.g., to call `reload_if_changed` on the shader files
+    pub fn program_mut(&mut self) -> &mut ShaderProgram {
+        &mut self.program
     }
 
-    pub fn add_element<T>(&mut self, gl: &gl::Gl, mode: gl::types::GLenum, elem2vtx: &Vec<T>)
+    pub fn add_element<T>(&mut self, gl: &Rc<gl::Gl>, mode: gl::types::GLenum, elem2vtx: &Vec<T>)
     where
         T: 'static + Copy + num_traits::AsPrimitive<gl::types::GLuint>,
     {
-        use crate::gl::types::GLuint;
-        unsafe {
-            gl.BindVertexArray(self.vao);
-            let mut ebo0 = std::mem::zeroed();
-            gl.GenBuffers(1, &mut ebo0);
-            gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo0);
-            let elem2vtx0: Vec<GLuint> = elem2vtx.iter().map(|i| (*i).as_()).collect();
-            gl.BufferData(
-                gl::ELEMENT_ARRAY_BUFFER,
-                (elem2vtx0.len() * std::mem::size_of::<GLuint>()) as gl::types::GLsizeiptr,
-                elem2vtx0.as_ptr() as *const _,
-                gl::STATIC_DRAW,
-            );
-            self.ebo.mode = mode;
-            self.ebo.elem_size = elem2vtx0.len();
-            self.ebo.ebo = ebo0;
+        if let Some(vao) = &self.vao {
+            vao.bind();
         }
+        let mut ebo = Buffer::new(gl, gl::ELEMENT_ARRAY_BUFFER);
+        let index_type = ebo.set_indices(elem2vtx, BufferUsage::Static);
+        self.ebo = Some(ElementBufferObject {
+            mode,
+            elem_size: elem2vtx.len(),
+            index_type,
+            ebo,
+        });
     }
 
-    pub fn update_vertex(&mut self, gl: &gl::Gl, vtx_xyz: &Vec<f32>, ndim: i32) {
+    pub fn update_vertex(&mut self, gl: &Rc<gl::Gl>, vtx_xyz: &Vec<f32>, ndim: i32) {
         self.ndim = ndim;
-        unsafe {
-            gl.BindVertexArray(self.vao);
-            //
-            let mut vbo = std::mem::zeroed();
-            gl.GenBuffers(1, &mut vbo);
-            gl.BindBuffer(gl::ARRAY_BUFFER, vbo);
-            gl.BufferData(
-                gl::ARRAY_BUFFER,
-                (vtx_xyz.len() * std::mem::size_of::<f32>()) as gl::types::GLsizeiptr,
-                vtx_xyz.as_ptr() as *const _,
-                gl::STATIC_DRAW,
-            );
-            let pos_attrib = gl.GetAttribLocation(self.program, b"position\0".as_ptr() as *const _);
-            gl.EnableVertexAttribArray(pos_attrib as gl::types::GLuint);
-            gl.VertexAttribPointer(
-                pos_attrib as gl::types::GLuint,
-                self.ndim,
-                gl::FLOAT,
-                0,
-                self.ndim * std::mem::size_of::<f32>() as gl::types::GLsizei,
-                std::ptr::null(),
-            );
+        if let Some(vao) = &self.vao {
+            vao.bind();
         }
+        let vbo = self
+            .vbo_xyz
+            .get_or_insert_with(|| Buffer::new(gl, gl::ARRAY_BUFFER));
+        vbo.set_data(vtx_xyz, self.usage);
+        let float_size = std::mem::size_of::<f32>();
+        VertexLayout::new(self.ndim as usize * float_size)
+            .with_float("position", self.ndim, 0)
+            .bind(gl, &self.program);
     }
 
-    pub fn update_value(&mut self, gl: &gl::Gl, vtx_val: &Vec<f32>) {
-        unsafe {
-            gl.BindVertexArray(self.vao);
-            let mut vbo = std::mem::zeroed();
-            gl.GenBuffers(1, &mut vbo);
-            gl.BindBuffer(gl::ARRAY_BUFFER, vbo);
-            gl.BufferData(
-                gl::ARRAY_BUFFER,
-                (vtx_val.len() * std::mem::size_of::<f32>()) as gl::types::GLsizeiptr,
-                vtx_val.as_ptr() as *const _,
-                gl::STATIC_DRAW,
-            );
-            let val_attrib = gl.GetAttribLocation(self.program, b"value\0".as_ptr() as *const _);
-            gl.EnableVertexAttribArray(val_attrib as gl::types::GLuint);
-            gl.VertexAttribPointer(
-                val_attrib as gl::types::GLuint,
-                1,
-                gl::FLOAT,
-                0,
-                1 * std::mem::size_of::<f32>() as gl::types::GLsizei,
-                std::ptr::null(),
-            );
+    pub fn update_value(&mut self, gl: &Rc<gl::Gl>, vtx_val: &Vec<f32>) {
+        if let Some(vao) = &self.vao {
+            vao.bind();
         }
+        let vbo = self
+            .vbo_val
+            .get_or_insert_with(|| Buffer::new(gl, gl::ARRAY_BUFFER));
+        vbo.set_data(vtx_val, self.usage);
+        VertexLayout::new(std::mem::size_of::<f32>())
+            .with_float("value", 1, 0)
+            .bind(gl, &self.program);
     }
 
     pub fn draw(&self, gl: &gl::Gl, mat_modelview: &[f32], mat_projection: &[f32]) {
-        let mp0 = mat_projection;
-        let mp1: [f32; 16] = [
-            // mp1 = [z flip] * mp0
-            mp0[0], mp0[1], -mp0[2], mp0[3], mp0[4], mp0[5], -mp0[6], mp0[7], mp0[8], mp0[9],
-            -mp0[10], mp0[11], mp0[12], mp0[13], -mp0[14], mp0[15],
-        ];
+        let mat_modelview: &[f32; 16] = mat_modelview.try_into().unwrap();
+        let mat_projection: &[f32; 16] = mat_projection.try_into().unwrap();
+        let Some(ebo) = &self.ebo else {
+            return;
+        };
         unsafe {
-            gl.UseProgram(self.program);
-            gl.BindVertexArray(self.vao);
-            gl.UniformMatrix4fv(self.loc_mat_modelview, 1, gl::FALSE, mat_modelview.as_ptr());
-            gl.UniformMatrix4fv(self.loc_mat_projection, 1, gl::FALSE, mp1.as_ptr());
-            gl.Uniform1f(self.loc_val_min, self.val_min);
-            gl.Uniform1f(self.loc_val_max, self.val_max);
-            gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ebo.ebo);
+            gl.UseProgram(self.program.id());
+            if let Some(vao) = &self.vao {
+                vao.bind();
+            }
+            self.program.set_if_active(gl, "matMV", mat_modelview);
+            self.program.set_if_active(gl, "matPrj", mat_projection);
+            if self.color_map.len() == self.ncolor {
+                self.program
+                    .set_if_active(gl, "colors", self.color_map.as_slice());
+            } else {
+                let colors = crate::colormap::resample(&self.color_map, self.ncolor);
+                self.program.set_if_active(gl, "colors", colors.as_slice());
+            }
+            self.program.set_if_active(gl, "val_min", &self.val_min);
+            self.program.set_if_active(gl, "val_max", &self.val_max);
+            self.program
+                .set_if_active(gl, "flat_shading", &self.flat_shading);
+            ebo.ebo.bind();
             gl.DrawElements(
-                self.ebo.mode,
-                self.ebo.elem_size as i32,
-                gl::UNSIGNED_INT,
+                ebo.mode,
+                ebo.elem_size as i32,
+                ebo.index_type,
                 std::ptr::null(),
             );
         }
+        crate::debug::check_error(gl, "DrawElements in drawer_mesh_colormap");
     }
 }
@@ -1,232 +1,243 @@
 //! draw mesh position. The RGB color is defined par index
 
+use crate::buffer::{Buffer, BufferUsage, VertexArray};
+use crate::drawer_mesh::{ElementId, Elements};
 use crate::gl;
+use crate::render_state::{RenderState, SavedState};
+use crate::shader_program::{ShaderProgram, ShaderSource};
+use crate::vertex_layout::VertexLayout;
+use std::rc::Rc;
+
+/// `VTX_TEX`, `POINT_SIZE` and `Z_FLIP` are defined
+const VS_SRC: &[u8] = b"
+#include \"mesh_vertex\"
+\0";
+
+const FS_SRC: &[u8] = b"
+uniform sampler2D myTextureSampler;
+uniform vec3 color;
+uniform bool is_texture;
+
+in vec2 texPrj;
+out vec4 FragColor;
+
+void main() {
+    if( is_texture ){
+        FragColor = texture(myTextureSampler,texPrj);
+    }
+    else {
+        FragColor = vec4(color, 1.0);
+    }
+}
+\0";
 
 struct ElementBufferObject {
     mode: gl::types::GLenum,
     elem_size: usize,
-    ebo: gl::types::GLuint,
+    /// `gl::UNSIGNED_INT`, `gl::UNSIGNED_SHORT` or `gl::UNSIGNED_BYTE`
+    index_type: gl::types::GLenum,
+    ebo: Buffer,
     color: Option<[f32; 3]>,
+    state: RenderState,
 }
 
 pub struct Drawer {
-    program: gl::types::GLuint,
+    program: ShaderProgram,
     pub ndim: i32,
     num_point: i32,
-    vao: gl::types::GLuint,
-    // uniform variables
-    loc_texture: gl::types::GLint,
-    loc_color: gl::types::GLint,
-    loc_is_texture: gl::types::GLint,
-    loc_mat_modelview: gl::types::GLint,
-    loc_mat_projection: gl::types::GLint,
+    /// usage hint of the vertex buffers (see [`BufferUsage`])
+    pub usage: BufferUsage,
+    vao: Option<VertexArray>,
+    vbo_xyz: Option<Buffer>,
+    vbo_tex: Option<Buffer>,
     // elemenb buffer object
-    ebos: Vec<ElementBufferObject>,
+    ebos: Elements<ElementBufferObject>,
 }
 
 impl Drawer {
     pub fn new() -> Self {
+        let mut program = ShaderProgram::new(
+            ShaderSource::from_bytes(VS_SRC),
+            ShaderSource::from_bytes(FS_SRC),
+        );
+        program.preprocessor.define("VTX_TEX", 1);
+        program.preprocessor.define("POINT_SIZE", 1);
+        program.preprocessor.define("Z_FLIP", 1);
         Drawer {
-            program: 0,
+            program,
             ndim: 0,
             num_point: 0,
-            vao: 0,
-            loc_texture: -1,    // -1 is the failure flag
-            loc_color: -1,      // -1 is the failure flag
-            loc_is_texture: -1, // -1 is the failure flag
-            loc_mat_modelview: -1,
-            loc_mat_projection: -1,
-            ebos: Vec::<ElementBufferObject>::new(),
+            usage: BufferUsage::Static,
+            vao: None,
+            vbo_xyz: None,
+            vbo_tex: None,
+            ebos: Elements::new(),
         }
     }
 
-    pub fn compile_shader(&mut self, gl: &gl::Gl) {
-        const VS_SRC: &[u8] = b"
-#version 330
-
-uniform mat4 matMV;
-uniform mat4 matPrj;
-
-layout (location = 0) in vec3 position;
-layout (location = 1) in vec2 texIn;
-out vec2 texPrj;
-
-void main() {
-    gl_Position = matPrj * matMV * vec4(position, 1.0);
-    texPrj = texIn;
-    // gl_Position = vec4(position, 1.0);
-}
-\0";
-
-        const FS_SRC: &[u8] = b"
-#version 330
-
-uniform sampler2D myTextureSampler;
-uniform vec3 color;
-uniform bool is_texture;
-
-in vec2 texPrj;
-out vec4 FragColor;
-
-void main() {
-    if( is_texture ){
-        FragColor = texture(myTextureSampler,texPrj);
+    pub fn compile_shader(&mut self, gl: &Rc<gl::Gl>) -> Result<(), crate::ShaderError> {
+        self.program.compile(gl)?;
+        if self.vao.is_none() {
+            self.vao = Some(VertexArray::new(gl));
+        }
+        Ok(())
     }
-    else {
-        FragColor = vec4(color, 1.0);
+
+    /// compile the shaders from files instead of the embedded sources.
+    /// The files are watched by `ShaderProgram::reload_if_changed` (see `program_mut`)
+    pub fn compile_shader_from_files<P: Into<std::path::PathBuf>>(
+        &mut self,
+        gl: &Rc<gl::Gl>,
+        path_vertex: P,
+        path_fragment: P,
+    ) -> Result<(), crate::ShaderError> {
+        self.program.set_sources(
+            ShaderSource::from_path(path_vertex),
+            ShaderSource::from_path(path_fragment),
+        );
+        self.compile_shader(gl)
     }
-}
-\0";
-        use crate::utility::{compile_shaders, get_uniform_location};
-        unsafe {
-            self.program = compile_shaders(gl, VS_SRC, FS_SRC);
-            self.loc_mat_modelview = get_uniform_location(gl, "matMV", self.program);
-            self.loc_mat_projection = get_uniform_location(gl, "matPrj", self.program);
-            self.loc_texture = get_uniform_location(gl, "myTextureSampler", self.program);
-            self.loc_color = get_uniform_location(gl, "color", self.program);
-            self.loc_is_texture = get_uniform_location(gl, "is_texture", self.program);
-
-            if gl.BindVertexArray.is_loaded() {
-                let mut vao0 = std::mem::zeroed();
-                gl.GenVertexArrays(1, &mut vao0);
-                self.vao = vao0;
-                gl.BindVertexArray(self.vao);
-            }
-        }
+
+    /// program of the drawer, e.g., to call `reload_if_changed` on the shader files
+    pub fn program_mut(&mut self) -> &mut ShaderProgram {
+        &mut self.program
     }
 
+    /// add the elements drawn in `color`, or with the texture if `color` is `None`
     pub fn add_element<T>(
         &mut self,
-        gl: &gl::Gl,
+        gl: &Rc<gl::Gl>,
         mode: gl::types::GLenum,
         elem2vtx: &Vec<T>,
         color: Option<[f32; 3]>,
-    ) where
+    ) -> ElementId
+    where
         T: 'static + Copy + num_traits::AsPrimitive<gl::types::GLuint>,
     {
-        use crate::gl::types::GLuint;
-        unsafe {
-            gl.BindVertexArray(self.vao);
-            let mut ebo0 = std::mem::zeroed();
-            gl.GenBuffers(1, &mut ebo0);
-            gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo0);
-            let elem_vtx0: Vec<GLuint> = elem2vtx.iter().map(|i| (*i).as_()).collect();
-            gl.BufferData(
-                gl::ELEMENT_ARRAY_BUFFER,
-                (elem_vtx0.len() * std::mem::size_of::<usize>()) as gl::types::GLsizeiptr,
-                elem_vtx0.as_ptr() as *const _,
-                gl::STATIC_DRAW,
-            );
-            self.ebos.push(ElementBufferObject {
-                mode,
-                elem_size: elem_vtx0.len(),
-                ebo: ebo0,
-                color,
-            });
+        self.add_element_with_state(gl, mode, elem2vtx, color, RenderState::default())
+    }
+
+    /// add the elements drawn in the render `state`. The elements are drawn in the order added
+    pub fn add_element_with_state<T>(
+        &mut self,
+        gl: &Rc<gl::Gl>,
+        mode: gl::types::GLenum,
+        elem2vtx: &[T],
+        color: Option<[f32; 3]>,
+        state: RenderState,
+    ) -> ElementId
+    where
+        T: 'static + Copy + num_traits::AsPrimitive<gl::types::GLuint>,
+    {
+        if let Some(vao) = &self.vao {
+            vao.bind();
         }
+        let mut ebo = Buffer::new(gl, gl::ELEMENT_ARRAY_BUFFER);
+        let index_type = ebo.set_indices(elem2vtx, BufferUsage::Static);
+        self.ebos.push(ElementBufferObject {
+            mode,
+            elem_size: elem2vtx.len(),
+            index_type,
+            ebo,
+            color,
+            state,
+        })
     }
 
-    pub fn update_vertex(&mut self, gl: &gl::Gl, vtx2xyz: &Vec<f32>, ndim: i32) {
+    /// `None` if the elements are removed
+    pub fn render_state_mut(&mut self, id: ElementId) -> Option<&mut RenderState> {
+        self.ebos.get_mut(id).map(|e| &mut e.state)
+    }
+
+    /// remove the elements and delete the buffer. Returns `false` if already removed
+    pub fn remove_element(&mut self, id: ElementId) -> bool {
+        self.ebos.remove(id)
+    }
+
+    pub fn update_vertex(&mut self, gl: &Rc<gl::Gl>, vtx2xyz: &Vec<f32>, ndim: i32) {
         self.ndim = ndim;
         self.num_point = vtx2xyz.len() as i32 / self.ndim;
-        unsafe {
-            gl.BindVertexArray(self.vao);
-            //
-            let mut vbo = std::mem::zeroed();
-            gl.GenBuffers(1, &mut vbo);
-            gl.BindBuffer(gl::ARRAY_BUFFER, vbo);
-            gl.BufferData(
-                gl::ARRAY_BUFFER,
-                (vtx2xyz.len() * std::mem::size_of::<f32>()) as gl::types::GLsizeiptr,
-                vtx2xyz.as_ptr() as *const _,
-                gl::STATIC_DRAW,
-            );
-
-            let pos_attrib = gl.GetAttribLocation(self.program, b"position\0".as_ptr() as *const _);
-            gl.EnableVertexAttribArray(pos_attrib as gl::types::GLuint);
-            gl.VertexAttribPointer(
-                pos_attrib as gl::types::GLuint,
-                self.ndim,
-                gl::FLOAT,
-                0,
-                self.ndim * std::mem::size_of::<f32>() as gl::types::GLsizei,
-                std::ptr::null(),
-            );
+        if let Some(vao) = &self.vao {
+            vao.bind();
         }
+        let vbo = self
+            .vbo_xyz
+            .get_or_insert_with(|| Buffer::new(gl, gl::ARRAY_BUFFER));
+        vbo.set_data(vtx2xyz, self.usage);
+        let float_size = std::mem::size_of::<f32>();
+        VertexLayout::new(self.ndim as usize * float_size)
+            .with_float("position", self.ndim, 0)
+            .bind(gl, &self.program);
     }
 
-    pub fn set_texture_uv(&mut self, gl: &gl::Gl, vtx2tex: &Vec<f32>) {
-        unsafe {
-            gl.BindVertexArray(self.vao);
-
-            let mut vbo = std::mem::zeroed();
-            gl.GenBuffers(1, &mut vbo);
-            gl.BindBuffer(gl::ARRAY_BUFFER, vbo);
-            gl.BufferData(
-                gl::ARRAY_BUFFER,
-                (vtx2tex.len() * std::mem::size_of::<f32>()) as gl::types::GLsizeiptr,
-                vtx2tex.as_ptr() as *const _,
-                gl::STATIC_DRAW,
-            );
-            let pos_attrib = gl.GetAttribLocation(self.program, b"texIn\0".as_ptr() as *const _);
-            gl.EnableVertexAttribArray(pos_attrib as gl::types::GLuint);
-            gl.VertexAttribPointer(
-                1,
-                2,
-                gl::FLOAT,
-                gl::FALSE,
-                2 * std::mem::size_of::<f32>() as gl::types::GLsizei,
-                std::ptr::null(),
-            ); // gl24
+    pub fn set_texture_uv(&mut self, gl: &Rc<gl::Gl>, vtx2tex: &Vec<f32>) {
+        if let Some(vao) = &self.vao {
+            vao.bind();
         }
+        let vbo = self
+            .vbo_tex
+            .get_or_insert_with(|| Buffer::new(gl, gl::ARRAY_BUFFER));
+        vbo.set_data(vtx2tex, self.usage);
+        VertexLayout::new(2 * std::mem::size_of::<f32>())
+            .with_float("texIn", 2, 0)
+            .bind(gl, &self.program);
     }
 
     pub fn draw(&self, gl: &gl::Gl, mat_modelview: &[f32], mat_projection: &[f32]) {
-        let mp0 = mat_projection;
-        let mp1: [f32; 16] = [
-            // mp1 = [z flip] * mp0
-            mp0[0], mp0[1], -mp0[2], mp0[3], mp0[4], mp0[5], -mp0[6], mp0[7], mp0[8], mp0[9],
-            -mp0[10], mp0[11], mp0[12], mp0[13], -mp0[14], mp0[15],
-        ];
+        let mat_modelview: &[f32; 16] = mat_modelview.try_into().unwrap();
+        let mat_projection: &[f32; 16] = mat_projection.try_into().unwrap();
+        let is_es = self.program.compiled_dialect().is_some_and(|d| d.is_es());
+        let saved = SavedState::query(gl, is_es);
+        let mut state = saved.state;
         unsafe {
-            gl.UseProgram(self.program);
-            gl.BindVertexArray(self.vao);
-            for ebo in &self.ebos {
+            gl.UseProgram(self.program.id());
+            if let Some(vao) = &self.vao {
+                vao.bind();
+            }
+            self.program.set_if_active(gl, "matMV", mat_modelview);
+            self.program.set_if_active(gl, "matPrj", mat_projection);
+            self.program.set_if_active(gl, "myTextureSampler", &0i32);
+            for ebo in self.ebos.iter() {
+                let ebo_state = ebo.state.effective();
+                ebo_state.transit(gl, &state, is_es);
+                state = ebo_state;
+                self.program
+                    .set_if_active(gl, "point_size", &ebo.state.point_size);
                 match ebo.color {
                     Some(color) => {
-                        gl.Uniform1i(self.loc_is_texture, 0);
-                        gl.Uniform3f(self.loc_color, color[0], color[1], color[2]);
+                        self.program.set_if_active(gl, "is_texture", &false);
+                        self.program.set_if_active(gl, "color", &color);
                     }
                     _ => {
-                        gl.Uniform1i(self.loc_is_texture, 1);
+                        self.program.set_if_active(gl, "is_texture", &true);
                     }
                 }
-                gl.UniformMatrix4fv(self.loc_mat_modelview, 1, gl::FALSE, mat_modelview.as_ptr());
-                gl.UniformMatrix4fv(self.loc_mat_projection, 1, gl::FALSE, mp1.as_ptr());
-                gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo.ebo);
+                ebo.ebo.bind();
                 gl.DrawElements(
                     ebo.mode,
                     ebo.elem_size as i32,
-                    gl::UNSIGNED_INT,
+                    ebo.index_type,
                     std::ptr::null(),
                 );
             }
         }
+        saved.restore(gl, &state, is_es);
+        crate::debug::check_error(gl, "DrawElements in drawer_mesh_tex");
     }
 
     pub fn draw_points(&self, gl: &gl::Gl, mat_modelview: &[f32], mat_projection: &[f32]) {
-        let mp0 = mat_projection;
-        let mp1: [f32; 16] = [
-            // mp1 = [z flip] * mp0
-            mp0[0], mp0[1], -mp0[2], mp0[3], mp0[4], mp0[5], -mp0[6], mp0[7], mp0[8], mp0[9],
-            -mp0[10], mp0[11], mp0[12], mp0[13], -mp0[14], mp0[15],
-        ];
+        let mat_modelview: &[f32; 16] = mat_modelview.try_into().unwrap();
+        let mat_projection: &[f32; 16] = mat_projection.try_into().unwrap();
         unsafe {
-            gl.UseProgram(self.program);
-            gl.BindVertexArray(self.vao);
-            gl.UniformMatrix4fv(self.loc_mat_modelview, 1, gl::FALSE, mat_modelview.as_ptr());
-            gl.UniformMatrix4fv(self.loc_mat_projection, 1, gl::FALSE, mp1.as_ptr());
+            gl.UseProgram(self.program.id());
+            if let Some(vao) = &self.vao {
+                vao.bind();
+            }
+            self.program.set_if_active(gl, "matMV", mat_modelview);
+            self.program.set_if_active(gl, "matPrj", mat_projection);
+            self.program.set_if_active(gl, "point_size", &1f32);
             gl.DrawArrays(gl::POINTS, 0, self.num_point);
         }
+        crate::debug::check_error(gl, "DrawArrays in drawer_mesh_tex");
     }
 }
@@ -0,0 +1,338 @@
+//! offscreen framebuffer object with texture color attachments and
+//! a depth renderbuffer or texture, deleted on drop
+
+use crate::gl;
+use crate::texture::{Filter, Texture, TextureDesc, TextureFormat};
+use std::rc::Rc;
+
+/// owned renderbuffer object deleted on drop
+pub struct Renderbuffer {
+    gl: Rc<gl::Gl>,
+    id: gl::types::GLuint,
+}
+
+impl Renderbuffer {
+    /// allocate the storage of `format` (e.g., `gl::DEPTH24_STENCIL8`)
+    pub fn new(gl: &Rc<gl::Gl>, format: gl::types::GLenum, width: usize, height: usize) -> Self {
+        let mut id = 0;
+        unsafe {
+            gl.GenRenderbuffers(1, &mut id);
+            gl.BindRenderbuffer(gl::RENDERBUFFER, id);
+            gl.RenderbufferStorage(
+                gl::RENDERBUFFER,
+                format,
+                width as gl::types::GLsizei,
+                height as gl::types::GLsizei,
+            );
+            gl.BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+        Renderbuffer {
+            gl: Rc::clone(gl),
+            id,
+        }
+    }
+
+    pub fn id(&self) -> gl::types::GLuint {
+        self.id
+    }
+}
+
+impl Drop for Renderbuffer {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteRenderbuffers(1, &self.id);
+        }
+    }
+}
+
+/// how the depth (and stencil) buffer is attached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthAttachment {
+    None,
+    /// renderbuffer that can not be sampled but may be faster
+    Renderbuffer(TextureFormat),
+    /// texture that can be sampled after rendering (e.g., shadow map)
+    Texture(TextureFormat),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FramebufferDesc {
+    pub width: usize,
+    pub height: usize,
+    /// formats of the color attachments `COLOR_ATTACHMENT0`, `COLOR_ATTACHMENT1`, ...
+    pub colors: Vec<TextureFormat>,
+    pub depth: DepthAttachment,
+}
+
+impl FramebufferDesc {
+    /// no attachment. Add them with `with_color` and `with_depth`
+    pub fn new(width: usize, height: usize) -> Self {
+        FramebufferDesc {
+            width,
+            height,
+            colors: vec![],
+            depth: DepthAttachment::None,
+        }
+    }
+
+    pub fn with_color(mut self, format: TextureFormat) -> Self {
+        self.colors.push(format);
+        self
+    }
+
+    pub fn with_depth(mut self, depth: DepthAttachment) -> Self {
+        self.depth = depth;
+        self
+    }
+}
+
+/// the framebuffer is not complete or the description is invalid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FramebufferError {
+    /// status returned by `CheckFramebufferStatus`
+    Incomplete(gl::types::GLenum),
+    /// a depth format given as color attachment or vice versa
+    InvalidFormat(TextureFormat),
+    /// more color attachments than `MAX_COLOR_ATTACHMENTS`
+    TooManyColorAttachments { num: usize, max: usize },
+}
+
+impl std::fmt::Display for FramebufferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FramebufferError::Incomplete(status) => {
+                let reason = match *status {
+                    gl::FRAMEBUFFER_UNDEFINED => "undefined",
+                    gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => "incomplete attachment",
+                    gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => "missing attachment",
+                    gl::FRAMEBUFFER_UNSUPPORTED => "unsupported combination of formats",
+                    gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => "inconsistent multisample",
+                    gl::FRAMEBUFFER_INCOMPLETE_DIMENSIONS => "inconsistent dimensions",
+                    _ => "unknown status",
+                };
+                write!(f, "framebuffer is not complete: {reason} ({status:#x})")
+            }
+            FramebufferError::InvalidFormat(format) => {
+                write!(f, "{format:?} can not be used for this attachment")
+            }
+            FramebufferError::TooManyColorAttachments { num, max } => {
+                write!(f, "{num} color attachments exceed the maximum {max}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FramebufferError {}
+
+enum DepthBuffer {
+    None,
+    Renderbuffer(Renderbuffer),
+    Texture(Texture),
+}
+
+pub struct Framebuffer {
+    gl: Rc<gl::Gl>,
+    id: gl::types::GLuint,
+    desc: FramebufferDesc,
+    colors: Vec<Texture>,
+    depth: DepthBuffer,
+}
+
+impl Framebuffer {
+    /// create the framebuffer and its attachments. The framebuffer bound before is bound again
+    /// on return
+    pub fn new(gl: &Rc<gl::Gl>, desc: &FramebufferDesc) -> Result<Self, FramebufferError> {
+        if let Some(&format) = desc.colors.iter().find(|f| f.is_depth()) {
+            return Err(FramebufferError::InvalidFormat(format));
+        }
+        if let DepthAttachment::Renderbuffer(format) | DepthAttachment::Texture(format) = desc.depth
+        {
+            if !format.is_depth() {
+                return Err(FramebufferError::InvalidFormat(format));
+            }
+        }
+        let mut max_color: gl::types::GLint = 0;
+        unsafe {
+            gl.GetIntegerv(gl::MAX_COLOR_ATTACHMENTS, &mut max_color);
+        }
+        if desc.colors.len() > max_color as usize {
+            return Err(FramebufferError::TooManyColorAttachments {
+                num: desc.colors.len(),
+                max: max_color as usize,
+            });
+        }
+        let mut id = 0;
+        unsafe {
+            gl.GenFramebuffers(1, &mut id);
+        }
+        let mut fb = Framebuffer {
+            gl: Rc::clone(gl),
+            id,
+            desc: desc.clone(),
+            colors: vec![],
+            depth: DepthBuffer::None,
+        };
+        fb.allocate()?;
+        Ok(fb)
+    }
+
+    /// (re)create the attachments in the size of `self.desc` and check the completeness.
+    /// The binding of the caller (e.g., a parent offscreen target) is restored
+    fn allocate(&mut self) -> Result<(), FramebufferError> {
+        let gl = &self.gl;
+        let (w, h) = (self.desc.width, self.desc.height);
+        self.colors = self
+            .desc
+            .colors
+            .iter()
+            .map(|&format| Texture::from_desc::<u8>(gl, &TextureDesc::new(w, h, format), None))
+            .collect();
+        self.depth = match self.desc.depth {
+            DepthAttachment::None => DepthBuffer::None,
+            DepthAttachment::Renderbuffer(format) => {
+                let (internal, _, _) = format.to_gl();
+                DepthBuffer::Renderbuffer(Renderbuffer::new(
+                    gl,
+                    internal as gl::types::GLenum,
+                    w,
+                    h,
+                ))
+            }
+            DepthAttachment::Texture(format) => {
+                let desc = TextureDesc::new(w, h, format).filter(Filter::Nearest, Filter::Nearest);
+                DepthBuffer::Texture(Texture::from_desc::<u8>(gl, &desc, None))
+            }
+        };
+        let depth_attachment = match self.desc.depth {
+            DepthAttachment::Renderbuffer(TextureFormat::Depth24Stencil8)
+            | DepthAttachment::Texture(TextureFormat::Depth24Stencil8) => {
+                gl::DEPTH_STENCIL_ATTACHMENT
+            }
+            _ => gl::DEPTH_ATTACHMENT,
+        };
+        let status = unsafe {
+            let mut prev: gl::types::GLint = 0;
+            gl.GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut prev);
+            gl.BindFramebuffer(gl::FRAMEBUFFER, self.id);
+            let mut draw_buffers = vec![];
+            for (i, tex) in self.colors.iter().enumerate() {
+                let attachment = gl::COLOR_ATTACHMENT0 + i as gl::types::GLenum;
+                gl.FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, tex.id(), 0);
+                draw_buffers.push(attachment);
+            }
+            match &self.depth {
+                DepthBuffer::None => {}
+                DepthBuffer::Renderbuffer(rb) => gl.FramebufferRenderbuffer(
+                    gl::FRAMEBUFFER,
+                    depth_attachment,
+                    gl::RENDERBUFFER,
+                    rb.id(),
+                ),
+                DepthBuffer::Texture(tex) => gl.FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    depth_attachment,
+                    gl::TEXTURE_2D,
+                    tex.id(),
+                    0,
+                ),
+            }
+            if draw_buffers.is_empty() {
+                draw_buffers.push(gl::NONE);
+            }
+            gl.DrawBuffers(
+                draw_buffers.len() as gl::types::GLsizei,
+                draw_buffers.as_ptr(),
+            );
+            let status = gl.CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl.BindFramebuffer(gl::FRAMEBUFFER, prev as gl::types::GLuint);
+            status
+        };
+        crate::debug::check_error(gl, "allocating framebuffer");
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(FramebufferError::Incomplete(status));
+        }
+        Ok(())
+    }
+
+    pub fn id(&self) -> gl::types::GLuint {
+        self.id
+    }
+
+    /// name shown in the driver messages (see `crate::debug`)
+    pub fn set_label(&self, label: &str) {
+        crate::debug::object_label(&self.gl, gl::FRAMEBUFFER, self.id, label);
+    }
+
+    pub fn desc(&self) -> &FramebufferDesc {
+        &self.desc
+    }
+
+    pub fn width(&self) -> usize {
+        self.desc.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.desc.height
+    }
+
+    /// texture of the `i`-th color attachment
+    pub fn color(&self, i: usize) -> &Texture {
+        &self.colors[i]
+    }
+
+    /// depth texture if the depth is attached as `DepthAttachment::Texture`
+    pub fn depth(&self) -> Option<&Texture> {
+        match &self.depth {
+            DepthBuffer::Texture(tex) => Some(tex),
+            _ => None,
+        }
+    }
+
+    /// render into this framebuffer. The viewport is set to the whole framebuffer
+    pub fn bind(&self) {
+        unsafe {
+            self.gl.BindFramebuffer(gl::FRAMEBUFFER, self.id);
+            self.gl.Viewport(
+                0,
+                0,
+                self.desc.width as gl::types::GLsizei,
+                self.desc.height as gl::types::GLsizei,
+            );
+        }
+    }
+
+    /// render into the window surface again. The viewport is not restored
+    pub fn unbind(&self) {
+        unsafe {
+            self.gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    /// reallocate the attachments in the new size. The content is lost.
+    /// The binding is kept, so this can be called while rendering into another framebuffer.
+    /// On error, the attachments are allocated again in the previous size
+    pub fn resize(&mut self, width: usize, height: usize) -> Result<(), FramebufferError> {
+        if width == self.desc.width && height == self.desc.height {
+            return Ok(());
+        }
+        let (prev_width, prev_height) = (self.desc.width, self.desc.height);
+        self.desc.width = width;
+        self.desc.height = height;
+        if let Err(e) = self.allocate() {
+            self.desc.width = prev_width;
+            self.desc.height = prev_height;
+            // the previous size was complete, so this only fails if the context is lost
+            let _ = self.allocate();
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteFramebuffers(1, &self.id);
+        }
+    }
+}
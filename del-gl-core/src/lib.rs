@@ -7,38 +7,236 @@ pub mod gl {
     pub use Gles2 as Gl;
 }
 
+/// Stage of the shader pipeline where compilation or linking failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Geometry,
+    Fragment,
+    Link,
+    /// shader type not supported by this crate (e.g., `gl::COMPUTE_SHADER`)
+    Unsupported(gl::types::GLenum),
+}
+
+impl ShaderStage {
+    fn from_shader_type(shader_type: gl::types::GLenum) -> Self {
+        match shader_type {
+            gl::VERTEX_SHADER => ShaderStage::Vertex,
+            gl::GEOMETRY_SHADER => ShaderStage::Geometry,
+            gl::FRAGMENT_SHADER => ShaderStage::Fragment,
+            _ => ShaderStage::Unsupported(shader_type),
+        }
+    }
+}
+
+impl std::fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderStage::Vertex => write!(f, "vertex shader"),
+            ShaderStage::Geometry => write!(f, "geometry shader"),
+            ShaderStage::Fragment => write!(f, "fragment shader"),
+            ShaderStage::Link => write!(f, "program link"),
+            ShaderStage::Unsupported(shader_type) => {
+                write!(f, "unsupported shader type {:#x}", shader_type)
+            }
+        }
+    }
+}
+
+/// Error of shader compilation or program linking.
+///
+/// `log` is the complete info log reported by the driver, and `annotated_source`
+/// lists the source lines referenced by the log (empty for link errors).
+#[derive(Debug, Clone)]
+pub struct ShaderError {
+    pub stage: ShaderStage,
+    pub log: String,
+    pub annotated_source: String,
+}
+
+impl std::fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} error", self.stage)?;
+        writeln!(f, "{}", self.log.trim_end())?;
+        if !self.annotated_source.is_empty() {
+            write!(f, "{}", self.annotated_source)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+/// extract the source string and line numbers from an info log line.
+/// handles the `0:12(5): error` (Mesa), `0(12) : error` (NVIDIA) and
+/// `ERROR: 0:12: ...` (AMD, Intel, Apple) styles.
+fn line_number_in_log(log_line: &str) -> Option<(usize, usize)> {
+    let s = log_line.trim_start();
+    let s = s
+        .strip_prefix("ERROR:")
+        .or_else(|| s.strip_prefix("WARNING:"))
+        .unwrap_or(s)
+        .trim_start();
+    let string: String = s.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let s = &s[string.len()..];
+    let s = s.strip_prefix(':').or_else(|| s.strip_prefix('('))?;
+    let digits: String = s.chars().take_while(|c| c.is_ascii_digit()).collect();
+    Some((string.parse().ok()?, digits.parse().ok()?))
+}
+
+/// line of `source` numbered as (source string, line) by the `#line` directives
+fn lines_numbered_by_directives(source: &str) -> std::collections::BTreeMap<(usize, usize), usize> {
+    let mut offset = 0;
+    let mut res = std::collections::BTreeMap::new();
+    let (mut string, mut line) = (0, 1);
+    for (iline, src_line) in source.lines().enumerate() {
+        let trimmed = src_line.trim_start();
+        if let Some(version) = trimmed.strip_prefix("#version") {
+            offset = shader_preprocessor::line_directive_offset(version.trim());
+        }
+        res.entry((string, line)).or_insert(iline + 1);
+        line += 1;
+        if let Some(args) = trimmed.strip_prefix("#line") {
+            let mut args = args.split_whitespace().map(|a| a.parse::<usize>().ok());
+            if let Some(Some(n)) = args.next() {
+                line = n + offset;
+            }
+            if let Some(Some(n)) = args.next() {
+                string = n;
+            }
+        }
+    }
+    res
+}
+
+/// list the source lines referenced in the info log with two lines of context.
+/// The line numbers in the log are mapped to those of `source` through the `#line` directives.
+/// the referenced lines are marked with `>>` and followed by the log messages
+fn annotate_source(source: &str, log: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let numbered = lines_numbered_by_directives(source);
+    let mut line2msgs = std::collections::BTreeMap::<usize, Vec<&str>>::new();
+    for log_line in log.lines() {
+        if let Some(&iline) = line_number_in_log(log_line).and_then(|n| numbered.get(&n)) {
+            line2msgs.entry(iline).or_default().push(log_line.trim());
+        }
+    }
+    let mut res = String::new();
+    let mut iline_last = 0;
+    for (&iline, msgs) in &line2msgs {
+        let first = iline.saturating_sub(2).max(iline_last + 1).max(1);
+        let last = (iline + 2).min(lines.len());
+        if first > iline_last + 1 && iline_last != 0 {
+            res += "    ...\n";
+        }
+        for jline in first..=last {
+            let mark = if jline == iline { ">>" } else { "  " };
+            res += &format!("{} {:4} | {}\n", mark, jline, lines[jline - 1]);
+            if jline == iline {
+                for msg in msgs {
+                    res += &format!("          {}\n", msg);
+                }
+            }
+        }
+        iline_last = last;
+    }
+    res
+}
+
+unsafe fn shader_info_log(gl: &gl::Gl, shader: gl::types::GLuint) -> String {
+    let mut len: gl::types::GLint = 0;
+    gl.GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut len);
+    let mut info_log = vec![0u8; len.max(1) as usize];
+    let mut size: GLsizei = 0;
+    gl.GetShaderInfoLog(shader, len, &mut size, info_log.as_mut_ptr().cast());
+    info_log.truncate(size.max(0) as usize);
+    String::from_utf8_lossy(&info_log).into_owned()
+}
+
+unsafe fn program_info_log(gl: &gl::Gl, program: gl::types::GLuint) -> String {
+    let mut len: gl::types::GLint = 0;
+    gl.GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut len);
+    let mut info_log = vec![0u8; len.max(1) as usize];
+    let mut size: GLsizei = 0;
+    gl.GetProgramInfoLog(program, len, &mut size, info_log.as_mut_ptr().cast());
+    info_log.truncate(size.max(0) as usize);
+    String::from_utf8_lossy(&info_log).into_owned()
+}
+
+/// compile a shader of type `shader` (e.g., `gl::VERTEX_SHADER`).
+/// The `source` may or may not be terminated by the null character.
+///
+/// # Safety
+/// The GL context of `gl` needs to be current.
 pub unsafe fn create_shader(
     gl: &gl::Gl,
     shader: gl::types::GLenum,
     source: &[u8],
-) -> gl::types::GLuint {
+) -> Result<gl::types::GLuint, ShaderError> {
+    let stage = ShaderStage::from_shader_type(shader);
+    if let ShaderStage::Unsupported(_) = stage {
+        return Err(ShaderError {
+            stage,
+            log: "only the vertex, geometry and fragment shaders are supported".to_string(),
+            annotated_source: String::new(),
+        });
+    }
+    let source = match source.iter().position(|&c| c == 0) {
+        Some(len) => &source[..len],
+        None => source,
+    };
     let shader = gl.CreateShader(shader);
     gl.ShaderSource(
         shader,
         1,
         [source.as_ptr().cast()].as_ptr(),
-        std::ptr::null(),
+        [source.len() as gl::types::GLint].as_ptr(),
     );
     gl.CompileShader(shader);
-    {
-        // show error message if fails
-        let mut success: gl::types::GLint = 0;
-        gl.GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
-        if success != 1 {
-            let mut info_log = [0i8; 512];
-            let mut size: GLsizei = 0;
-            gl.GetShaderInfoLog(shader, 512, &mut size, info_log.as_mut_ptr());
-            let info_log: Vec<u8> = info_log
-                .iter()
-                .take_while(|&v| *v != 0)
-                .map(|&v| v as u8)
-                .collect();
-            let a = std::str::from_utf8(&info_log).unwrap();
-            println!("Shader Compile Error! --> {}", a);
-            panic!();
-        }
+    let mut success: gl::types::GLint = 0;
+    gl.GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
+    if success == 0 {
+        let log = shader_info_log(gl, shader);
+        gl.DeleteShader(shader);
+        let source = String::from_utf8_lossy(source);
+        return Err(ShaderError {
+            stage,
+            annotated_source: annotate_source(&source, &log),
+            log,
+        });
+    }
+    Ok(shader)
+}
+
+/// link the compiled shaders into a program. The shaders are deleted regardless of the result.
+///
+/// # Safety
+/// The GL context of `gl` needs to be current.
+pub unsafe fn link_program(
+    gl: &gl::Gl,
+    shaders: &[gl::types::GLuint],
+) -> Result<gl::types::GLuint, ShaderError> {
+    let program = gl.CreateProgram();
+    for &shader in shaders {
+        gl.AttachShader(program, shader);
+    }
+    gl.LinkProgram(program);
+    for &shader in shaders {
+        gl.DetachShader(program, shader);
+        gl.DeleteShader(shader);
     }
-    shader
+    let mut success: gl::types::GLint = 0;
+    gl.GetProgramiv(program, gl::LINK_STATUS, &mut success);
+    if success == 0 {
+        let log = program_info_log(gl, program);
+        gl.DeleteProgram(program);
+        return Err(ShaderError {
+            stage: ShaderStage::Link,
+            log,
+            annotated_source: String::new(),
+        });
+    }
+    Ok(program)
 }
 
 pub fn print_info(gl: &gl::Gl) {
@@ -58,36 +256,12 @@ pub fn set_shader_program(
     gl: &gl::Gl,
     vertex_shader_source: &[u8],
     fragment_shader_source: &[u8],
-) -> gl::types::GLuint {
+) -> Result<gl::types::GLuint, ShaderError> {
     unsafe {
-        let vertex_shader = create_shader(&gl, gl::VERTEX_SHADER, vertex_shader_source);
-        let fragment_shader = create_shader(&gl, gl::FRAGMENT_SHADER, fragment_shader_source);
-        let program = gl.CreateProgram();
-        gl.AttachShader(program, vertex_shader);
-        gl.AttachShader(program, fragment_shader);
-        gl.LinkProgram(program);
-        /*
-        {
-            // this will always fail even though the shader programs successfully links
-            let mut success: gl::types::GLint = 0;
-            gl.GetShaderiv(program, gl::LINK_STATUS, &mut success);
-            dbg!(success);
-            if success == 0 {
-                let mut infoLog = [0i8;512];
-                let mut size : GLsizei = 0;
-                gl.GetShaderInfoLog(program, 512, &mut size, infoLog.as_mut_ptr());
-                let infoLog: Vec<u8> = infoLog.iter().take_while(|&v| *v != 0 )
-                    .map(|&v| v as u8 ).collect();
-                let a = std::str::from_utf8(&infoLog).unwrap();
-                println!("Shader Link Error! --> {}", a);
-                panic!();
-            }
-        }
-         */
+        let program =
+            crate::utility::compile_shaders(gl, vertex_shader_source, fragment_shader_source)?;
         gl.UseProgram(program);
-        gl.DeleteShader(vertex_shader);
-        gl.DeleteShader(fragment_shader);
-        program
+        Ok(program)
     }
 }
 
@@ -98,11 +272,50 @@ pub fn get_gl_string(gl: &gl::Gl, variant: gl::types::GLenum) -> Option<&'static
     }
 }
 
+pub mod buffer;
+pub mod colormap;
+pub mod debug;
 pub mod drawer_array;
 pub mod drawer_array_xyrgb;
 pub mod drawer_array_xyzuv;
 pub mod drawer_mesh;
 pub mod drawer_mesh_colormap;
 pub mod drawer_mesh_tex;
+pub mod framebuffer;
+pub mod lighting;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock_gl;
+pub mod oit;
+pub mod readback;
+pub mod reflection;
+pub mod render_state;
+pub mod shader_preprocessor;
+pub mod shader_program;
+pub mod texture;
 pub mod utility;
+pub mod vertex_layout;
 pub mod view_ui_state;
+
+/// check if the context is OpenGL ES from the `VERSION` string (e.g., "OpenGL ES 3.2 Mesa")
+pub fn is_gles(gl: &gl::Gl) -> bool {
+    get_gl_string(gl, gl::VERSION).is_some_and(|v| v.to_bytes().starts_with(b"OpenGL ES"))
+}
+
+/// check if the context supports the extension `name` (e.g., "GL_EXT_texture_filter_anisotropic")
+pub fn has_extension(gl: &gl::Gl, name: &str) -> bool {
+    unsafe {
+        if gl.GetStringi.is_loaded() {
+            let mut num: gl::types::GLint = 0;
+            gl.GetIntegerv(gl::NUM_EXTENSIONS, &mut num);
+            return (0..num as gl::types::GLuint).any(|i| {
+                let s = gl.GetStringi(gl::EXTENSIONS, i);
+                !s.is_null() && CStr::from_ptr(s.cast()).to_bytes() == name.as_bytes()
+            });
+        }
+    }
+    get_gl_string(gl, gl::EXTENSIONS).is_some_and(|s| {
+        s.to_string_lossy()
+            .split_ascii_whitespace()
+            .any(|ext| ext == name)
+    })
+}
@@ -0,0 +1,172 @@
+//! materials, directional lights and vertex normals for the Blinn-Phong shading
+//! of the `lighting` shader snippet
+
+use crate::gl;
+
+/// maximum number of the directional lights. `MAX_LIGHT` in the shaders
+pub const MAX_LIGHT: usize = 4;
+
+/// color of the back faces of `Material::new`, distinct from usual mesh colors
+/// so that inverted or non-manifold parts stand out
+pub const DEFAULT_BACK_COLOR: [f32; 3] = [0.9, 0.6, 0.1];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Material {
+    /// color added regardless of the lights
+    pub ambient: [f32; 3],
+    /// color of the front faces. Also the flat color of the unlit elements
+    pub diffuse: [f32; 3],
+    pub specular: [f32; 3],
+    /// exponent of the specular highlight
+    pub shininess: f32,
+    /// diffuse color of the back faces in the two-sided lighting
+    pub back: [f32; 3],
+    /// opacity. Blended only if the `blend` of the render state is set
+    pub alpha: f32,
+}
+
+impl Material {
+    /// dim ambient and white specular highlight on `diffuse`
+    pub fn new(diffuse: [f32; 3]) -> Self {
+        Material {
+            ambient: diffuse.map(|c| c * 0.2),
+            diffuse,
+            specular: [0.3; 3],
+            shininess: 32.0,
+            back: DEFAULT_BACK_COLOR,
+            alpha: 1.0,
+        }
+    }
+
+    pub fn ambient(mut self, ambient: [f32; 3]) -> Self {
+        self.ambient = ambient;
+        self
+    }
+
+    pub fn specular(mut self, specular: [f32; 3], shininess: f32) -> Self {
+        self.specular = specular;
+        self.shininess = shininess;
+        self
+    }
+
+    pub fn back(mut self, back: [f32; 3]) -> Self {
+        self.back = back;
+        self
+    }
+
+    pub fn alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirectionalLight {
+    /// direction toward the light
+    pub direction: [f32; 3],
+    pub color: [f32; 3],
+    /// `direction` is in the view coordinate and the light moves with the camera.
+    /// Otherwise it is in the model coordinate
+    pub in_view: bool,
+}
+
+impl DirectionalLight {
+    /// white light from the camera
+    pub fn headlight() -> Self {
+        DirectionalLight {
+            direction: [0.0, 0.0, 1.0],
+            color: [1.0; 3],
+            in_view: true,
+        }
+    }
+
+    /// light fixed to the model
+    pub fn new(direction: [f32; 3], color: [f32; 3]) -> Self {
+        DirectionalLight {
+            direction,
+            color,
+            in_view: false,
+        }
+    }
+
+    /// direction in the view coordinate for the column-major model-view matrix
+    pub fn direction_in_view(&self, mat_modelview: &[f32; 16]) -> [f32; 3] {
+        if self.in_view {
+            return self.direction;
+        }
+        let m = mat_modelview;
+        let d = self.direction;
+        std::array::from_fn(|i| m[i] * d[0] + m[4 + i] * d[1] + m[8 + i] * d[2])
+    }
+}
+
+/// set the uniforms `ambient`, `color` (diffuse), `specular`, `shininess`, `back_color`
+/// and `alpha`.
+/// The program needs to be in use
+pub(crate) fn set_material(
+    gl: &gl::Gl,
+    program: &crate::shader_program::ShaderProgram,
+    material: &Material,
+) {
+    program.set_if_active(gl, "ambient", &material.ambient);
+    program.set_if_active(gl, "color", &material.diffuse);
+    program.set_if_active(gl, "specular", &material.specular);
+    program.set_if_active(gl, "shininess", &material.shininess);
+    program.set_if_active(gl, "back_color", &material.back);
+    program.set_if_active(gl, "alpha", &material.alpha);
+}
+
+/// set the uniforms `num_light`, `light_dirs` and `light_colors`.
+/// The lights after the first `MAX_LIGHT` are ignored. The program needs to be in use
+pub(crate) fn set_lights(
+    gl: &gl::Gl,
+    program: &crate::shader_program::ShaderProgram,
+    lights: &[DirectionalLight],
+    mat_modelview: &[f32; 16],
+) {
+    let lights = &lights[..lights.len().min(MAX_LIGHT)];
+    program.set_if_active(gl, "num_light", &(lights.len() as i32));
+    if lights.is_empty() {
+        return;
+    }
+    let dirs: Vec<[f32; 3]> = lights
+        .iter()
+        .map(|l| l.direction_in_view(mat_modelview))
+        .collect();
+    let colors: Vec<[f32; 3]> = lights.iter().map(|l| l.color).collect();
+    program.set_if_active(gl, "light_dirs", dirs.as_slice());
+    program.set_if_active(gl, "light_colors", colors.as_slice());
+}
+
+/// area-weighted normals of the vertices of a triangle mesh in 3D.
+/// The vertices not referenced by any triangle have zero normals
+pub fn vtx2normal_from_triangles<T>(tri2vtx: &[T], vtx2xyz: &[f32]) -> Vec<f32>
+where
+    T: 'static + Copy + num_traits::AsPrimitive<usize>,
+{
+    let mut vtx2nrm = vec![0f32; vtx2xyz.len()];
+    for tri in tri2vtx.chunks_exact(3) {
+        let iv: [usize; 3] = [tri[0].as_(), tri[1].as_(), tri[2].as_()];
+        let p: [[f32; 3]; 3] = iv.map(|i| [vtx2xyz[i * 3], vtx2xyz[i * 3 + 1], vtx2xyz[i * 3 + 2]]);
+        let e1 = [p[1][0] - p[0][0], p[1][1] - p[0][1], p[1][2] - p[0][2]];
+        let e2 = [p[2][0] - p[0][0], p[2][1] - p[0][1], p[2][2] - p[0][2]];
+        // twice the area times the unit normal
+        let n = [
+            e1[1] * e2[2] - e1[2] * e2[1],
+            e1[2] * e2[0] - e1[0] * e2[2],
+            e1[0] * e2[1] - e1[1] * e2[0],
+        ];
+        for i in iv {
+            for k in 0..3 {
+                vtx2nrm[i * 3 + k] += n[k];
+            }
+        }
+    }
+    for nrm in vtx2nrm.chunks_exact_mut(3) {
+        let len = (nrm[0] * nrm[0] + nrm[1] * nrm[1] + nrm[2] * nrm[2]).sqrt();
+        if len > 0.0 {
+            nrm.iter_mut().for_each(|c| *c /= len);
+        }
+    }
+    vtx2nrm
+}
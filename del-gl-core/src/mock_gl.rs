@@ -0,0 +1,868 @@
+//! recording fake of the GL function table to test the drawers without a driver.
+//!
+//! `load` returns a shared `gl::Gl` whose functions log the calls and simulate the creation
+//! and deletion of the objects. The state is kept per thread like a current context,
+//! so the tests running in parallel do not interfere.
+//! The shaders always compile and every `uniform` and vertex `in` declared in the
+//! sources (after resolving `#ifdef`) is reported as active, including the unused ones.
+//! The capabilities of `Enable` and the depth mask are kept, and the other queries
+//! return the initial values of GL.
+//! The functions not used in this crate are not loaded and panic if called.
+//! Only compiled with the `mock` feature, which the tests of this crate enable.
+//!
+//! ```ignore
+//! let gl = del_gl_core::mock_gl::load();
+//! let mut drawer = del_gl_core::drawer_mesh::Drawer::new();
+//! drawer.compile_shader(&gl).unwrap();
+//! drawer.draw(&gl, &mat_mv, &mat_prj);
+//! let num_draw = del_gl_core::mock_gl::calls_named("DrawElements").len();
+//! ```
+
+use crate::gl;
+use crate::gl::types::{GLenum, GLint, GLuint};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+/// kind of the GL objects tracked by the fake
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    Buffer,
+    VertexArray,
+    Texture,
+    Framebuffer,
+    Renderbuffer,
+    Shader,
+    Program,
+}
+
+/// a logged GL call. `name` is the function name without the `gl` prefix
+/// (e.g., `"DrawElements"`). The integer arguments are stored as they are,
+/// the floats as their bits and the pointers as their addresses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    pub name: &'static str,
+    pub args: Vec<i64>,
+}
+
+/// active uniform or attribute found in the shader sources
+struct Variable {
+    /// with `[0]` for arrays as reported by the drivers
+    name: String,
+    type_: GLenum,
+    size: GLint,
+    location: GLint,
+}
+
+#[derive(Default)]
+struct Program {
+    shaders: Vec<GLuint>,
+    uniforms: Vec<Variable>,
+    attributes: Vec<Variable>,
+}
+
+#[derive(Default)]
+struct State {
+    calls: Vec<Call>,
+    last_id: GLuint,
+    objects: BTreeMap<GLuint, ObjectKind>,
+    /// type and source of the shaders
+    shaders: BTreeMap<GLuint, (GLenum, String)>,
+    programs: BTreeMap<GLuint, Program>,
+    /// capabilities enabled by `Enable`
+    caps: std::collections::BTreeSet<GLenum>,
+    /// `DepthMask(FALSE)` is called last
+    depth_write_off: bool,
+}
+
+impl State {
+    fn create(&mut self, kind: ObjectKind) -> GLuint {
+        self.last_id += 1;
+        self.objects.insert(self.last_id, kind);
+        self.last_id
+    }
+
+    fn delete(&mut self, id: GLuint) {
+        // zero and unknown names are silently ignored as in GL
+        self.objects.remove(&id);
+        self.shaders.remove(&id);
+        self.programs.remove(&id);
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<State> = RefCell::new(State::default());
+}
+
+fn with_state<R>(f: impl FnOnce(&mut State) -> R) -> R {
+    STATE.with(|state| f(&mut state.borrow_mut()))
+}
+
+/// reset the state of this thread and load the fake functions
+pub fn load() -> std::rc::Rc<gl::Gl> {
+    with_state(|state| *state = State::default());
+    std::rc::Rc::new(gl::Gl::load_with(|symbol| {
+        symbol
+            .strip_prefix("gl")
+            .and_then(stubs::proc_address)
+            .unwrap_or(std::ptr::null())
+    }))
+}
+
+/// calls logged since `load` or the last `take_calls`
+pub fn calls() -> Vec<Call> {
+    with_state(|state| state.calls.clone())
+}
+
+/// calls of the function `name` (e.g., `"DrawElements"`)
+pub fn calls_named(name: &str) -> Vec<Call> {
+    with_state(|state| {
+        state
+            .calls
+            .iter()
+            .filter(|c| c.name == name)
+            .cloned()
+            .collect()
+    })
+}
+
+/// return the logged calls and clear the log. The objects are kept
+pub fn take_calls() -> Vec<Call> {
+    with_state(|state| std::mem::take(&mut state.calls))
+}
+
+/// number of the objects of `kind` created and not deleted yet
+pub fn num_live(kind: ObjectKind) -> usize {
+    with_state(|state| state.objects.values().filter(|&&k| k == kind).count())
+}
+
+fn glsl_type(name: &str) -> Option<GLenum> {
+    let type_ = match name {
+        "float" => gl::FLOAT,
+        "vec2" => gl::FLOAT_VEC2,
+        "vec3" => gl::FLOAT_VEC3,
+        "vec4" => gl::FLOAT_VEC4,
+        "int" => gl::INT,
+        "ivec2" => gl::INT_VEC2,
+        "ivec3" => gl::INT_VEC3,
+        "ivec4" => gl::INT_VEC4,
+        "uint" => gl::UNSIGNED_INT,
+        "bool" => gl::BOOL,
+        "mat2" => gl::FLOAT_MAT2,
+        "mat3" => gl::FLOAT_MAT3,
+        "mat4" => gl::FLOAT_MAT4,
+        "sampler2D" => gl::SAMPLER_2D,
+        "samplerCube" => gl::SAMPLER_CUBE,
+        _ => return None,
+    };
+    Some(type_)
+}
+
+/// find the `uniform` (and the `in`/`attribute` if `is_vertex`) declarations.
+/// `#define`, `#ifdef`, `#ifndef`, `#else` and `#endif` are resolved.
+/// The other conditions (`#if`) are assumed to be true
+fn declarations(source: &str, is_vertex: bool) -> (Vec<Variable>, Vec<Variable>) {
+    let mut uniforms = vec![];
+    let mut attributes = vec![];
+    let mut defines = BTreeMap::<&str, &str>::new();
+    // a line is active if all the enclosing conditions are true
+    let mut conditions: Vec<bool> = vec![];
+    for line in source.lines() {
+        let line = line.trim();
+        let active = conditions.iter().all(|&c| c);
+        if let Some(directive) = line.strip_prefix('#') {
+            let mut tokens = directive.split_whitespace();
+            let name = tokens.next().unwrap_or("");
+            let arg = tokens.next().unwrap_or("");
+            match name {
+                "ifdef" => conditions.push(defines.contains_key(arg)),
+                "ifndef" => conditions.push(!defines.contains_key(arg)),
+                "if" => conditions.push(true),
+                "else" => {
+                    if let Some(c) = conditions.last_mut() {
+                        *c = !*c;
+                    }
+                }
+                "endif" => {
+                    conditions.pop();
+                }
+                "define" if active => {
+                    defines.insert(arg, tokens.next().unwrap_or(""));
+                }
+                "undef" if active => {
+                    defines.remove(arg);
+                }
+                _ => {}
+            }
+            continue;
+        }
+        if !active {
+            continue;
+        }
+        let mut decl = line;
+        if let Some(rest) = decl.strip_prefix("layout") {
+            decl = rest.split_once(')').map_or("", |(_, d)| d.trim_start());
+        }
+        let Some(decl) = decl.strip_suffix(';') else {
+            continue;
+        };
+        let mut tokens = decl
+            .split_whitespace()
+            .filter(|t| !matches!(*t, "highp" | "mediump" | "lowp" | "flat"));
+        let (Some(qualifier), Some(type_), Some(name), None) =
+            (tokens.next(), tokens.next(), tokens.next(), tokens.next())
+        else {
+            continue;
+        };
+        let list = match qualifier {
+            "uniform" => &mut uniforms,
+            "in" | "attribute" if is_vertex => &mut attributes,
+            _ => continue,
+        };
+        let Some(type_) = glsl_type(type_) else {
+            continue;
+        };
+        let (name, size) = match name.split_once('[') {
+            Some((name, size)) => {
+                let size = size.trim_end_matches(']').trim();
+                let size = defines.get(size).copied().unwrap_or(size);
+                (format!("{}[0]", name), size.parse().unwrap_or(1))
+            }
+            None => (name.to_string(), 1),
+        };
+        if list.iter().any(|v: &Variable| v.name == name) {
+            continue;
+        }
+        let location = list.last().map_or(0, |v| v.location + v.size);
+        list.push(Variable {
+            name,
+            type_,
+            size,
+            location,
+        });
+    }
+    (uniforms, attributes)
+}
+
+/// location of `name`, `name[0]` or `name[i]`
+fn location(variables: &[Variable], name: &str) -> GLint {
+    let (base, index) = match name.split_once('[') {
+        Some((base, index)) => (base, index.trim_end_matches(']').parse().unwrap_or(-1)),
+        None => (name, 0),
+    };
+    variables
+        .iter()
+        .find(|v| v.name.strip_suffix("[0]").unwrap_or(&v.name) == base)
+        .filter(|v| (0..v.size).contains(&index))
+        .map_or(-1, |v| v.location + index)
+}
+
+/// conversion of the arguments to the logged value
+trait Arg {
+    fn to_arg(self) -> i64;
+}
+
+macro_rules! impl_arg_for_integer {
+    ($($t: ty),*) => {
+        $(impl Arg for $t {
+            fn to_arg(self) -> i64 {
+                self as i64
+            }
+        })*
+    };
+}
+
+impl_arg_for_integer!(i32, u32, u8, isize);
+
+impl Arg for f32 {
+    fn to_arg(self) -> i64 {
+        self.to_bits() as i64
+    }
+}
+
+impl<T> Arg for *const T {
+    fn to_arg(self) -> i64 {
+        self as usize as i64
+    }
+}
+
+impl<T> Arg for *mut T {
+    fn to_arg(self) -> i64 {
+        self as usize as i64
+    }
+}
+
+fn record(name: &'static str, args: Vec<i64>) {
+    with_state(|state| state.calls.push(Call { name, args }));
+}
+
+mod stubs {
+    #![allow(non_snake_case)]
+    use super::*;
+    use crate::gl::types::{GLboolean, GLchar, GLfloat, GLintptr, GLsizei, GLsizeiptr, GLubyte};
+    use std::ffi::c_void;
+
+    macro_rules! log {
+        ($name: ident $(, $arg: expr)*) => {
+            record(stringify!($name), vec![$(Arg::to_arg($arg)),*])
+        };
+    }
+
+    /// functions without side effect other than logging
+    macro_rules! logging_stubs {
+        ($($name: ident($($arg: ident: $t: ty),*);)*) => {
+            $(pub extern "system" fn $name($($arg: $t),*) {
+                log!($name $(, $arg)*);
+            })*
+        };
+    }
+
+    logging_stubs! {
+        ActiveTexture(texture: GLenum);
+        BindBuffer(target: GLenum, buffer: GLuint);
+        BindFramebuffer(target: GLenum, framebuffer: GLuint);
+        BindRenderbuffer(target: GLenum, renderbuffer: GLuint);
+        BindTexture(target: GLenum, texture: GLuint);
+        BindVertexArray(array: GLuint);
+        BlendFunc(sfactor: GLenum, dfactor: GLenum);
+        BlendFuncSeparate(src_rgb: GLenum, dst_rgb: GLenum, src_alpha: GLenum, dst_alpha: GLenum);
+        BlitFramebuffer(x0: GLint, y0: GLint, x1: GLint, y1: GLint, dx0: GLint, dy0: GLint, dx1: GLint, dy1: GLint, mask: GLenum, filter: GLenum);
+        BufferData(target: GLenum, size: GLsizeiptr, data: *const c_void, usage: GLenum);
+        BufferSubData(target: GLenum, offset: GLintptr, size: GLsizeiptr, data: *const c_void);
+        Clear(mask: GLenum);
+        ClearBufferfv(buffer: GLenum, drawbuffer: GLint, value: *const GLfloat);
+        ClearColor(r: GLfloat, g: GLfloat, b: GLfloat, a: GLfloat);
+        CompileShader(shader: GLuint);
+        CullFace(mode: GLenum);
+        DrawArrays(mode: GLenum, first: GLint, count: GLsizei);
+        DrawBuffers(n: GLsizei, bufs: *const GLenum);
+        DrawElements(mode: GLenum, count: GLsizei, type_: GLenum, indices: *const c_void);
+        EnableVertexAttribArray(index: GLuint);
+        Finish();
+        FramebufferRenderbuffer(target: GLenum, attachment: GLenum, rb_target: GLenum, rb: GLuint);
+        FramebufferTexture2D(target: GLenum, attachment: GLenum, tex_target: GLenum, tex: GLuint, level: GLint);
+        GenerateMipmap(target: GLenum);
+        LineWidth(width: GLfloat);
+        PixelStorei(pname: GLenum, param: GLint);
+        PolygonOffset(factor: GLfloat, units: GLfloat);
+        ReadBuffer(src: GLenum);
+        ReadPixels(x: GLint, y: GLint, w: GLsizei, h: GLsizei, format: GLenum, type_: GLenum, pixels: *mut c_void);
+        RenderbufferStorage(target: GLenum, format: GLenum, w: GLsizei, h: GLsizei);
+        TexImage2D(target: GLenum, level: GLint, internal: GLint, w: GLsizei, h: GLsizei, border: GLint, format: GLenum, type_: GLenum, pixels: *const c_void);
+        TexParameterf(target: GLenum, pname: GLenum, param: GLfloat);
+        TexParameteri(target: GLenum, pname: GLenum, param: GLint);
+        TexSubImage2D(target: GLenum, level: GLint, x: GLint, y: GLint, w: GLsizei, h: GLsizei, format: GLenum, type_: GLenum, pixels: *const c_void);
+        Uniform1f(location: GLint, v0: GLfloat);
+        Uniform1fv(location: GLint, count: GLsizei, value: *const GLfloat);
+        Uniform1i(location: GLint, v0: GLint);
+        Uniform1ui(location: GLint, v0: GLuint);
+        Uniform2fv(location: GLint, count: GLsizei, value: *const GLfloat);
+        Uniform3fv(location: GLint, count: GLsizei, value: *const GLfloat);
+        Uniform4fv(location: GLint, count: GLsizei, value: *const GLfloat);
+        UniformMatrix3fv(location: GLint, count: GLsizei, transpose: GLboolean, value: *const GLfloat);
+        UniformMatrix4fv(location: GLint, count: GLsizei, transpose: GLboolean, value: *const GLfloat);
+        UseProgram(program: GLuint);
+        VertexAttribDivisor(index: GLuint, divisor: GLuint);
+        VertexAttribIPointer(index: GLuint, size: GLint, type_: GLenum, stride: GLsizei, pointer: *const c_void);
+        VertexAttribPointer(index: GLuint, size: GLint, type_: GLenum, normalized: GLboolean, stride: GLsizei, pointer: *const c_void);
+        Viewport(x: GLint, y: GLint, w: GLsizei, h: GLsizei);
+    }
+
+    /// functions creating or deleting `n` objects of a kind
+    macro_rules! object_stubs {
+        ($($gen: ident, $delete: ident, $kind: ident;)*) => {
+            $(pub extern "system" fn $gen(n: GLsizei, ids: *mut GLuint) {
+                log!($gen, n, ids);
+                for i in 0..n.max(0) as usize {
+                    let id = with_state(|state| state.create(ObjectKind::$kind));
+                    unsafe { *ids.add(i) = id };
+                }
+            }
+
+            pub extern "system" fn $delete(n: GLsizei, ids: *const GLuint) {
+                log!($delete, n, ids);
+                for i in 0..n.max(0) as usize {
+                    let id = unsafe { *ids.add(i) };
+                    with_state(|state| state.delete(id));
+                }
+            })*
+        };
+    }
+
+    object_stubs! {
+        GenBuffers, DeleteBuffers, Buffer;
+        GenVertexArrays, DeleteVertexArrays, VertexArray;
+        GenTextures, DeleteTextures, Texture;
+        GenFramebuffers, DeleteFramebuffers, Framebuffer;
+        GenRenderbuffers, DeleteRenderbuffers, Renderbuffer;
+    }
+
+    pub extern "system" fn CreateShader(type_: GLenum) -> GLuint {
+        log!(CreateShader, type_);
+        with_state(|state| {
+            let id = state.create(ObjectKind::Shader);
+            state.shaders.insert(id, (type_, String::new()));
+            id
+        })
+    }
+
+    pub extern "system" fn DeleteShader(shader: GLuint) {
+        log!(DeleteShader, shader);
+        with_state(|state| state.delete(shader));
+    }
+
+    pub extern "system" fn ShaderSource(
+        shader: GLuint,
+        count: GLsizei,
+        string: *const *const GLchar,
+        length: *const GLint,
+    ) {
+        log!(ShaderSource, shader, count, string, length);
+        let mut source = String::new();
+        for i in 0..count.max(0) as usize {
+            let s = unsafe { *string.add(i) };
+            let len = if length.is_null() {
+                -1
+            } else {
+                unsafe { *length.add(i) }
+            };
+            let bytes = if len < 0 {
+                unsafe { std::ffi::CStr::from_ptr(s) }.to_bytes()
+            } else {
+                unsafe { std::slice::from_raw_parts(s.cast::<u8>(), len as usize) }
+            };
+            source += &String::from_utf8_lossy(bytes);
+        }
+        with_state(|state| {
+            if let Some(shader) = state.shaders.get_mut(&shader) {
+                shader.1 = source;
+            }
+        });
+    }
+
+    pub extern "system" fn GetShaderiv(shader: GLuint, pname: GLenum, params: *mut GLint) {
+        log!(GetShaderiv, shader, pname, params);
+        let value = match pname {
+            gl::COMPILE_STATUS => gl::TRUE as GLint,
+            gl::SHADER_TYPE => {
+                with_state(|state| state.shaders.get(&shader).map_or(0, |s| s.0)) as GLint
+            }
+            _ => 0,
+        };
+        unsafe { *params = value };
+    }
+
+    pub extern "system" fn GetShaderInfoLog(
+        shader: GLuint,
+        buf_size: GLsizei,
+        length: *mut GLsizei,
+        info_log: *mut GLchar,
+    ) {
+        log!(GetShaderInfoLog, shader, buf_size, length, info_log);
+        write_name("", buf_size, length, info_log);
+    }
+
+    pub extern "system" fn CreateProgram() -> GLuint {
+        log!(CreateProgram);
+        with_state(|state| {
+            let id = state.create(ObjectKind::Program);
+            state.programs.insert(id, Program::default());
+            id
+        })
+    }
+
+    pub extern "system" fn DeleteProgram(program: GLuint) {
+        log!(DeleteProgram, program);
+        with_state(|state| state.delete(program));
+    }
+
+    pub extern "system" fn AttachShader(program: GLuint, shader: GLuint) {
+        log!(AttachShader, program, shader);
+        with_state(|state| {
+            if let Some(program) = state.programs.get_mut(&program) {
+                program.shaders.push(shader);
+            }
+        });
+    }
+
+    pub extern "system" fn DetachShader(program: GLuint, shader: GLuint) {
+        log!(DetachShader, program, shader);
+        with_state(|state| {
+            if let Some(program) = state.programs.get_mut(&program) {
+                program.shaders.retain(|&s| s != shader);
+            }
+        });
+    }
+
+    pub extern "system" fn LinkProgram(program: GLuint) {
+        log!(LinkProgram, program);
+        with_state(|state| {
+            let State {
+                shaders, programs, ..
+            } = state;
+            let Some(program) = programs.get_mut(&program) else {
+                return;
+            };
+            program.uniforms.clear();
+            program.attributes.clear();
+            for (type_, source) in program.shaders.iter().filter_map(|s| shaders.get(s)) {
+                let (uniforms, attributes) = declarations(source, *type_ == gl::VERTEX_SHADER);
+                for u in uniforms {
+                    if program.uniforms.iter().all(|v| v.name != u.name) {
+                        let location = program.uniforms.last().map_or(0, |v| v.location + v.size);
+                        program.uniforms.push(Variable { location, ..u });
+                    }
+                }
+                program.attributes.extend(attributes);
+            }
+        });
+    }
+
+    pub extern "system" fn GetProgramiv(program: GLuint, pname: GLenum, params: *mut GLint) {
+        log!(GetProgramiv, program, pname, params);
+        let value = with_state(|state| {
+            let Some(p) = state.programs.get(&program) else {
+                return 0;
+            };
+            let max_length = |vs: &[Variable]| {
+                vs.iter()
+                    .map(|v| v.name.len() as GLint + 1)
+                    .max()
+                    .unwrap_or(0)
+            };
+            match pname {
+                gl::LINK_STATUS => gl::TRUE as GLint,
+                gl::ACTIVE_UNIFORMS => p.uniforms.len() as GLint,
+                gl::ACTIVE_ATTRIBUTES => p.attributes.len() as GLint,
+                gl::ACTIVE_UNIFORM_MAX_LENGTH => max_length(&p.uniforms),
+                gl::ACTIVE_ATTRIBUTE_MAX_LENGTH => max_length(&p.attributes),
+                _ => 0,
+            }
+        });
+        unsafe { *params = value };
+    }
+
+    pub extern "system" fn GetProgramInfoLog(
+        program: GLuint,
+        buf_size: GLsizei,
+        length: *mut GLsizei,
+        info_log: *mut GLchar,
+    ) {
+        log!(GetProgramInfoLog, program, buf_size, length, info_log);
+        write_name("", buf_size, length, info_log);
+    }
+
+    /// copy `s` with the null terminator, truncated to `buf_size`
+    fn write_name(s: &str, buf_size: GLsizei, length: *mut GLsizei, dst: *mut GLchar) {
+        let len = s.len().min((buf_size.max(1) - 1) as usize);
+        if buf_size > 0 {
+            unsafe {
+                std::ptr::copy_nonoverlapping(s.as_ptr(), dst.cast::<u8>(), len);
+                *dst.add(len) = 0;
+            }
+        }
+        if !length.is_null() {
+            unsafe { *length = len as GLsizei };
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn get_active(
+        program: GLuint,
+        index: GLuint,
+        is_uniform: bool,
+        buf_size: GLsizei,
+        length: *mut GLsizei,
+        size: *mut GLint,
+        type_: *mut GLenum,
+        name: *mut GLchar,
+    ) {
+        with_state(|state| {
+            let variable = state.programs.get(&program).and_then(|p| {
+                let vs = if is_uniform {
+                    &p.uniforms
+                } else {
+                    &p.attributes
+                };
+                vs.get(index as usize)
+            });
+            let (n, s, t) = variable.map_or(("", 0, 0), |v| (v.name.as_str(), v.size, v.type_));
+            write_name(n, buf_size, length, name);
+            unsafe {
+                *size = s;
+                *type_ = t;
+            }
+        });
+    }
+
+    pub extern "system" fn GetActiveUniform(
+        program: GLuint,
+        index: GLuint,
+        buf_size: GLsizei,
+        length: *mut GLsizei,
+        size: *mut GLint,
+        type_: *mut GLenum,
+        name: *mut GLchar,
+    ) {
+        log!(
+            GetActiveUniform,
+            program,
+            index,
+            buf_size,
+            length,
+            size,
+            type_,
+            name
+        );
+        get_active(program, index, true, buf_size, length, size, type_, name);
+    }
+
+    pub extern "system" fn GetActiveAttrib(
+        program: GLuint,
+        index: GLuint,
+        buf_size: GLsizei,
+        length: *mut GLsizei,
+        size: *mut GLint,
+        type_: *mut GLenum,
+        name: *mut GLchar,
+    ) {
+        log!(
+            GetActiveAttrib,
+            program,
+            index,
+            buf_size,
+            length,
+            size,
+            type_,
+            name
+        );
+        get_active(program, index, false, buf_size, length, size, type_, name);
+    }
+
+    fn get_location(program: GLuint, name: *const GLchar, is_uniform: bool) -> GLint {
+        let name = unsafe { std::ffi::CStr::from_ptr(name) }.to_string_lossy();
+        with_state(|state| {
+            state.programs.get(&program).map_or(-1, |p| {
+                let vs = if is_uniform {
+                    &p.uniforms
+                } else {
+                    &p.attributes
+                };
+                location(vs, &name)
+            })
+        })
+    }
+
+    pub extern "system" fn GetUniformLocation(program: GLuint, name: *const GLchar) -> GLint {
+        log!(GetUniformLocation, program, name);
+        get_location(program, name, true)
+    }
+
+    pub extern "system" fn GetAttribLocation(program: GLuint, name: *const GLchar) -> GLint {
+        log!(GetAttribLocation, program, name);
+        get_location(program, name, false)
+    }
+
+    pub extern "system" fn CheckFramebufferStatus(target: GLenum) -> GLenum {
+        log!(CheckFramebufferStatus, target);
+        gl::FRAMEBUFFER_COMPLETE
+    }
+
+    pub extern "system" fn GetError() -> GLenum {
+        log!(GetError);
+        gl::NO_ERROR
+    }
+
+    pub extern "system" fn GetIntegerv(pname: GLenum, data: *mut GLint) {
+        log!(GetIntegerv, pname, data);
+        let value = match pname {
+            gl::MAX_COLOR_ATTACHMENTS => 8,
+            gl::MAX_TEXTURE_SIZE => 16384,
+            gl::PACK_ALIGNMENT | gl::UNPACK_ALIGNMENT => 4,
+            gl::CULL_FACE_MODE => gl::BACK as GLint,
+            gl::BLEND_SRC_RGB | gl::BLEND_SRC_ALPHA => gl::ONE as GLint,
+            _ => 0,
+        };
+        unsafe { *data = value };
+    }
+
+    pub extern "system" fn GetFloatv(pname: GLenum, data: *mut GLfloat) {
+        log!(GetFloatv, pname, data);
+        let value = match pname {
+            gl::LINE_WIDTH => 1.0,
+            _ => 0.0,
+        };
+        unsafe { *data = value };
+    }
+
+    /// the window surface has a 24-bit depth, and nothing is attached to the framebuffers
+    pub extern "system" fn GetFramebufferAttachmentParameteriv(
+        target: GLenum,
+        attachment: GLenum,
+        pname: GLenum,
+        params: *mut GLint,
+    ) {
+        log!(
+            GetFramebufferAttachmentParameteriv,
+            target,
+            attachment,
+            pname,
+            params
+        );
+        let value = match (attachment, pname) {
+            (gl::DEPTH, gl::FRAMEBUFFER_ATTACHMENT_OBJECT_TYPE) => gl::FRAMEBUFFER_DEFAULT,
+            (gl::DEPTH, gl::FRAMEBUFFER_ATTACHMENT_DEPTH_SIZE) => 24,
+            (gl::DEPTH, gl::FRAMEBUFFER_ATTACHMENT_COMPONENT_TYPE) => gl::UNSIGNED_NORMALIZED,
+            _ => gl::NONE,
+        };
+        unsafe { *params = value as GLint };
+    }
+
+    pub extern "system" fn GetBooleanv(pname: GLenum, data: *mut GLboolean) {
+        log!(GetBooleanv, pname, data);
+        let value = match pname {
+            gl::DEPTH_WRITEMASK => with_state(|state| !state.depth_write_off),
+            _ => false,
+        };
+        unsafe { *data = value as GLboolean };
+    }
+
+    pub extern "system" fn Enable(cap: GLenum) {
+        log!(Enable, cap);
+        with_state(|state| state.caps.insert(cap));
+    }
+
+    pub extern "system" fn Disable(cap: GLenum) {
+        log!(Disable, cap);
+        with_state(|state| state.caps.remove(&cap));
+    }
+
+    pub extern "system" fn IsEnabled(cap: GLenum) -> GLboolean {
+        log!(IsEnabled, cap);
+        with_state(|state| state.caps.contains(&cap)) as GLboolean
+    }
+
+    pub extern "system" fn DepthMask(flag: GLboolean) {
+        log!(DepthMask, flag);
+        with_state(|state| state.depth_write_off = flag == gl::FALSE);
+    }
+
+    pub extern "system" fn GetString(name: GLenum) -> *const GLubyte {
+        log!(GetString, name);
+        let s: &'static [u8] = match name {
+            gl::VENDOR => b"del-gl\0",
+            gl::RENDERER => b"mock\0",
+            gl::VERSION => b"4.5 mock\0",
+            gl::SHADING_LANGUAGE_VERSION => b"4.50 mock\0",
+            gl::EXTENSIONS => b"\0",
+            _ => return std::ptr::null(),
+        };
+        s.as_ptr()
+    }
+
+    pub extern "system" fn GetStringi(name: GLenum, index: GLuint) -> *const GLubyte {
+        // no extension is reported by `NUM_EXTENSIONS`
+        log!(GetStringi, name, index);
+        std::ptr::null()
+    }
+
+    macro_rules! proc_address {
+        ($($name: ident),* $(,)?) => {
+            /// address of the stub of the function `name` without the `gl` prefix
+            pub fn proc_address(name: &str) -> Option<*const c_void> {
+                match name {
+                    $(stringify!($name) => Some($name as *const c_void),)*
+                    _ => None,
+                }
+            }
+        };
+    }
+
+    proc_address!(
+        ActiveTexture,
+        AttachShader,
+        BindBuffer,
+        BindFramebuffer,
+        BindRenderbuffer,
+        BindTexture,
+        BindVertexArray,
+        BlendFunc,
+        BlendFuncSeparate,
+        BlitFramebuffer,
+        BufferData,
+        BufferSubData,
+        CheckFramebufferStatus,
+        Clear,
+        ClearBufferfv,
+        ClearColor,
+        CompileShader,
+        CreateProgram,
+        CreateShader,
+        CullFace,
+        DeleteBuffers,
+        DeleteFramebuffers,
+        DeleteProgram,
+        DeleteRenderbuffers,
+        DeleteShader,
+        DeleteTextures,
+        DeleteVertexArrays,
+        DepthMask,
+        DetachShader,
+        Disable,
+        DrawArrays,
+        DrawBuffers,
+        DrawElements,
+        Enable,
+        EnableVertexAttribArray,
+        Finish,
+        FramebufferRenderbuffer,
+        FramebufferTexture2D,
+        GenBuffers,
+        GenFramebuffers,
+        GenRenderbuffers,
+        GenTextures,
+        GenVertexArrays,
+        GenerateMipmap,
+        GetActiveAttrib,
+        GetActiveUniform,
+        GetAttribLocation,
+        GetBooleanv,
+        GetError,
+        GetFloatv,
+        GetFramebufferAttachmentParameteriv,
+        GetIntegerv,
+        GetProgramInfoLog,
+        GetProgramiv,
+        GetShaderInfoLog,
+        GetShaderiv,
+        GetString,
+        GetStringi,
+        GetUniformLocation,
+        IsEnabled,
+        LineWidth,
+        LinkProgram,
+        PixelStorei,
+        PolygonOffset,
+        ReadBuffer,
+        ReadPixels,
+        RenderbufferStorage,
+        ShaderSource,
+        TexImage2D,
+        TexParameterf,
+        TexParameteri,
+        TexSubImage2D,
+        Uniform1f,
+        Uniform1fv,
+        Uniform1i,
+        Uniform1ui,
+        Uniform2fv,
+        Uniform3fv,
+        Uniform4fv,
+        UniformMatrix3fv,
+        UniformMatrix4fv,
+        UseProgram,
+        VertexAttribDivisor,
+        VertexAttribIPointer,
+        VertexAttribPointer,
+        Viewport,
+    );
+}
@@ -0,0 +1,265 @@
+//! weighted blended order-independent transparency (McGuire and Bavoil, JCGT 2013).
+//!
+//! The transparent elements are accumulated into two floating-point color attachments
+//! without sorting, and composited over the opaque scene:
+//!
+//! ```text
+//! drawer_opaque.draw(gl, mv, prj);
+//! target.begin(gl)?;
+//! drawer.draw_transparent(gl, mv, prj);
+//! target.end(gl);
+//! ```
+//!
+//! This needs GLSL 3.00 or later and renderable float textures
+//! (`EXT_color_buffer_half_float` in GLES)
+
+use crate::buffer::VertexArray;
+use crate::framebuffer::{DepthAttachment, Framebuffer, FramebufferDesc, FramebufferError};
+use crate::gl;
+use crate::render_state::SavedState;
+use crate::shader_program::{ShaderProgram, ShaderSource};
+use crate::texture::TextureFormat;
+use std::rc::Rc;
+
+/// full-screen triangle without vertex buffer
+const VS_SRC: &[u8] = b"
+void main() {
+    vec2 p = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    gl_Position = vec4(p * 2.0 - 1.0, 0.0, 1.0);
+}
+\0";
+
+/// average color of the transparent fragments, blended with the revealage in the alpha
+const FS_SRC: &[u8] = b"
+uniform sampler2D accum;
+uniform sampler2D weight;
+
+layout (location = 0) out vec4 FragColor;
+
+void main() {
+    ivec2 ij = ivec2(gl_FragCoord.xy);
+    vec4 a = texelFetch(accum, ij, 0);
+    if (a.a >= 1.0) {
+        discard; // no transparent fragment
+    }
+    float w = texelFetch(weight, ij, 0).r;
+    FragColor = vec4(a.rgb / max(w, 1.0e-5), a.a);
+}
+\0";
+
+#[derive(Debug)]
+pub enum OitError {
+    Framebuffer(FramebufferError),
+    Shader(crate::ShaderError),
+    /// the depth of the framebuffer bound at `begin` can not be copied into the target
+    IncompatibleDepth {
+        format: TextureFormat,
+        /// depth bits (zero without depth) and the samples of the bound framebuffer
+        depth_size: i32,
+        samples: i32,
+    },
+}
+
+impl std::fmt::Display for OitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OitError::Framebuffer(e) => write!(f, "{}", e),
+            OitError::Shader(e) => write!(f, "{}", e),
+            OitError::IncompatibleDepth {
+                format,
+                depth_size,
+                samples,
+            } => write!(
+                f,
+                "depth of {depth_size} bits with {samples} samples can not be copied into {format:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OitError {}
+
+impl From<FramebufferError> for OitError {
+    fn from(e: FramebufferError) -> Self {
+        OitError::Framebuffer(e)
+    }
+}
+
+impl From<crate::ShaderError> for OitError {
+    fn from(e: crate::ShaderError) -> Self {
+        OitError::Shader(e)
+    }
+}
+
+/// accumulation (premultiplied color times weight, and the revealage in the alpha) and
+/// weight (sum of alpha times weight in red) attachments with a copy of the opaque depth
+pub struct Target {
+    framebuffer: Framebuffer,
+    program: ShaderProgram,
+    vao: VertexArray,
+    depth: TextureFormat,
+    /// framebuffer and viewport recorded by `begin` to composite onto in `end`
+    dst: gl::types::GLuint,
+    viewport: [gl::types::GLint; 4],
+    /// depth and blend state recorded by `begin` to be restored in `end`
+    saved: Option<SavedState>,
+}
+
+impl Target {
+    /// `depth` needs to be the depth format of the framebuffer of the opaque scene,
+    /// which is single-sampled, because the depth is copied with `BlitFramebuffer`
+    pub fn new(
+        gl: &Rc<gl::Gl>,
+        width: usize,
+        height: usize,
+        depth: TextureFormat,
+    ) -> Result<Self, OitError> {
+        let desc = FramebufferDesc::new(width, height)
+            .with_color(TextureFormat::Rgba16F)
+            .with_color(TextureFormat::Rgba16F)
+            .with_depth(DepthAttachment::Renderbuffer(depth));
+        let framebuffer = Framebuffer::new(gl, &desc)?;
+        framebuffer.set_label("oit target");
+        let mut program = ShaderProgram::new(
+            ShaderSource::from_bytes(VS_SRC),
+            ShaderSource::from_bytes(FS_SRC),
+        );
+        program.compile(gl)?;
+        Ok(Target {
+            framebuffer,
+            program,
+            vao: VertexArray::new(gl),
+            depth,
+            dst: 0,
+            viewport: [0; 4],
+            saved: None,
+        })
+    }
+
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    /// start accumulating the transparent elements. The depth of the framebuffer bound now
+    /// is copied and the target is resized to its viewport, which needs to start at the origin.
+    /// The depth test is kept and the depth write is disabled until `end`.
+    /// Its depth needs to have the format given to `new` without multisampling
+    pub fn begin(&mut self, gl: &gl::Gl) -> Result<(), OitError> {
+        let mut dst: gl::types::GLint = 0;
+        unsafe {
+            gl.GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut dst);
+            gl.GetIntegerv(gl::VIEWPORT, self.viewport.as_mut_ptr());
+        }
+        self.dst = dst as gl::types::GLuint;
+        self.check_depth(gl)?;
+        let is_es = self.program.compiled_dialect().is_some_and(|d| d.is_es());
+        self.saved = Some(SavedState::query(gl, is_es));
+        let (width, height) = (self.viewport[2], self.viewport[3]);
+        self.framebuffer.resize(width as usize, height as usize)?;
+        unsafe {
+            gl.BindFramebuffer(gl::READ_FRAMEBUFFER, self.dst);
+            gl.BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.framebuffer.id());
+            gl.BlitFramebuffer(
+                0,
+                0,
+                width,
+                height,
+                0,
+                0,
+                width,
+                height,
+                gl::DEPTH_BUFFER_BIT,
+                gl::NEAREST,
+            );
+        }
+        self.framebuffer.bind();
+        unsafe {
+            gl.ClearBufferfv(gl::COLOR, 0, [0f32, 0., 0., 1.].as_ptr());
+            gl.ClearBufferfv(gl::COLOR, 1, [0f32; 4].as_ptr());
+            gl.DepthMask(gl::FALSE);
+            gl.Enable(gl::BLEND);
+            // the color and weight are summed, and the alpha of the first attachment
+            // is multiplied by (1 - alpha)
+            gl.BlendFuncSeparate(gl::ONE, gl::ONE, gl::ZERO, gl::ONE_MINUS_SRC_ALPHA);
+        }
+        crate::debug::check_error(gl, "beginning the transparent pass");
+        Ok(())
+    }
+
+    /// `BlitFramebuffer` fails unless the depth formats and the samples match.
+    /// The framebuffer bound at `begin` needs to be the draw framebuffer
+    fn check_depth(&self, gl: &gl::Gl) -> Result<(), OitError> {
+        // the depth of the window surface is named differently
+        let attachment = if self.dst == 0 {
+            gl::DEPTH
+        } else {
+            gl::DEPTH_ATTACHMENT
+        };
+        let query = |pname: gl::types::GLenum| {
+            let mut v: gl::types::GLint = 0;
+            unsafe {
+                gl.GetFramebufferAttachmentParameteriv(
+                    gl::DRAW_FRAMEBUFFER,
+                    attachment,
+                    pname,
+                    &mut v,
+                );
+            }
+            v
+        };
+        let mut samples: gl::types::GLint = 0;
+        unsafe { gl.GetIntegerv(gl::SAMPLES, &mut samples) };
+        let (depth_size, component_type) =
+            if query(gl::FRAMEBUFFER_ATTACHMENT_OBJECT_TYPE) == gl::NONE as gl::types::GLint {
+                (0, gl::NONE)
+            } else {
+                (
+                    query(gl::FRAMEBUFFER_ATTACHMENT_DEPTH_SIZE),
+                    query(gl::FRAMEBUFFER_ATTACHMENT_COMPONENT_TYPE) as gl::types::GLenum,
+                )
+            };
+        let expected = match self.depth {
+            TextureFormat::Depth32F => (32, gl::FLOAT),
+            _ => (24, gl::UNSIGNED_NORMALIZED),
+        };
+        if (depth_size, component_type) != expected || samples != 0 {
+            return Err(OitError::IncompatibleDepth {
+                format: self.depth,
+                depth_size,
+                samples,
+            });
+        }
+        Ok(())
+    }
+
+    /// composite the transparent elements over the framebuffer bound at `begin`
+    /// and restore the blend and depth state recorded at `begin`
+    pub fn end(&self, gl: &gl::Gl) {
+        let [x, y, width, height] = self.viewport;
+        unsafe {
+            gl.BindFramebuffer(gl::FRAMEBUFFER, self.dst);
+            gl.Viewport(x, y, width, height);
+            gl.BlendFunc(gl::ONE_MINUS_SRC_ALPHA, gl::SRC_ALPHA);
+            gl.Disable(gl::DEPTH_TEST);
+            gl.UseProgram(self.program.id());
+            self.vao.bind();
+            for (unit, name) in [(0, "accum"), (1, "weight")] {
+                gl.ActiveTexture(gl::TEXTURE0 + unit);
+                self.framebuffer.color(unit as usize).bind();
+                self.program.set_if_active(gl, name, &(unit as i32));
+            }
+            gl.DrawArrays(gl::TRIANGLES, 0, 3);
+            gl.ActiveTexture(gl::TEXTURE0);
+        }
+        if let Some(saved) = &self.saved {
+            unsafe {
+                if saved.state.depth_test {
+                    gl.Enable(gl::DEPTH_TEST);
+                }
+                gl.DepthMask(saved.state.depth_write as gl::types::GLboolean);
+            }
+            saved.blend.restore(gl);
+        }
+        crate::debug::check_error(gl, "DrawArrays in oit");
+    }
+}
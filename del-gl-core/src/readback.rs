@@ -0,0 +1,115 @@
+//! read the pixels of the default framebuffer or a `Framebuffer` back to the CPU.
+//! The rows are returned top-down, the same order as the images of
+//! `ImageGeneratorFrom3dCamPose::compute_image`, so that `img[(iw + ih * width) * 3]`
+//! is the pixel at the `iw`-th column of the `ih`-th row from the top
+
+use crate::framebuffer::Framebuffer;
+use crate::gl;
+
+/// reverse the order of the rows of `data` with rows of `row_len` elements
+pub fn flip_rows<T>(data: &mut [T], row_len: usize) {
+    if row_len == 0 {
+        return;
+    }
+    let height = data.len() / row_len;
+    for ih in 0..height / 2 {
+        let (upper, lower) = data.split_at_mut((height - 1 - ih) * row_len);
+        upper[ih * row_len..(ih + 1) * row_len].swap_with_slice(&mut lower[..row_len]);
+    }
+}
+
+/// bind `framebuffer` (or the default framebuffer if `None`) for reading,
+/// call `read` and restore the previous binding
+fn with_read_framebuffer<R>(
+    gl: &gl::Gl,
+    framebuffer: Option<&Framebuffer>,
+    read: impl FnOnce() -> R,
+) -> R {
+    let mut prev: gl::types::GLint = 0;
+    unsafe {
+        gl.GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut prev);
+        match framebuffer {
+            Some(fb) => {
+                gl.BindFramebuffer(gl::READ_FRAMEBUFFER, fb.id());
+                gl.ReadBuffer(gl::COLOR_ATTACHMENT0);
+            }
+            None => gl.BindFramebuffer(gl::READ_FRAMEBUFFER, 0),
+        }
+    }
+    let res = crate::texture::with_byte_alignment(gl, gl::PACK_ALIGNMENT, read);
+    unsafe {
+        gl.BindFramebuffer(gl::READ_FRAMEBUFFER, prev as gl::types::GLuint);
+    }
+    res
+}
+
+/// RGBA of the `img_shape.0`x`img_shape.1` pixels at the bottom-left corner.
+/// For a `Framebuffer`, the first color attachment is read
+pub fn read_pixels_rgba8(
+    gl: &gl::Gl,
+    framebuffer: Option<&Framebuffer>,
+    img_shape: (usize, usize),
+) -> Vec<u8> {
+    let (width, height) = img_shape;
+    let mut data = vec![0u8; width * height * 4];
+    with_read_framebuffer(gl, framebuffer, || unsafe {
+        gl.ReadPixels(
+            0,
+            0,
+            width as gl::types::GLsizei,
+            height as gl::types::GLsizei,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            data.as_mut_ptr() as *mut _,
+        );
+    });
+    flip_rows(&mut data, width * 4);
+    data
+}
+
+/// RGB of the `img_shape.0`x`img_shape.1` pixels at the bottom-left corner.
+/// This reads RGBA, which every context supports, and drops the alpha
+pub fn read_pixels_rgb8(
+    gl: &gl::Gl,
+    framebuffer: Option<&Framebuffer>,
+    img_shape: (usize, usize),
+) -> Vec<u8> {
+    read_pixels_rgba8(gl, framebuffer, img_shape)
+        .chunks_exact(4)
+        .flat_map(|rgba| [rgba[0], rgba[1], rgba[2]])
+        .collect()
+}
+
+/// check if `ReadPixels` can read `DEPTH_COMPONENT`. The desktop OpenGL always can,
+/// and OpenGL ES needs `GL_NV_read_depth`
+pub fn can_read_depth(gl: &gl::Gl) -> bool {
+    !crate::is_gles(gl) || crate::has_extension(gl, "GL_NV_read_depth")
+}
+
+/// depth in [0,1] of the `img_shape.0`x`img_shape.1` pixels at the bottom-left corner.
+/// `None` if the context can not read the depth (see `can_read_depth`)
+pub fn read_depth_f32(
+    gl: &gl::Gl,
+    framebuffer: Option<&Framebuffer>,
+    img_shape: (usize, usize),
+) -> Option<Vec<f32>> {
+    if !can_read_depth(gl) {
+        return None;
+    }
+    let (width, height) = img_shape;
+    let mut data = vec![0f32; width * height];
+    with_read_framebuffer(gl, framebuffer, || unsafe {
+        gl.ReadPixels(
+            0,
+            0,
+            width as gl::types::GLsizei,
+            height as gl::types::GLsizei,
+            gl::DEPTH_COMPONENT,
+            gl::FLOAT,
+            data.as_mut_ptr() as *mut _,
+        );
+    });
+    crate::debug::check_error(gl, "reading depth");
+    flip_rows(&mut data, width);
+    Some(data)
+}
@@ -0,0 +1,318 @@
+//! enumerate the active uniforms and attributes of a linked program,
+//! and set the uniform values with type validation
+
+use crate::gl;
+
+/// active uniform or attribute variable of a linked program
+#[derive(Debug, Clone)]
+pub struct ActiveVariable {
+    /// name without the `[0]` suffix of arrays
+    pub name: String,
+    /// GLSL type such as `gl::FLOAT_MAT4`
+    pub type_: gl::types::GLenum,
+    /// number of array elements (1 for non-array)
+    pub size: gl::types::GLint,
+    pub location: gl::types::GLint,
+}
+
+/// name of the GLSL type for error messages
+pub fn type_name(type_: gl::types::GLenum) -> &'static str {
+    match type_ {
+        gl::FLOAT => "float",
+        gl::FLOAT_VEC2 => "vec2",
+        gl::FLOAT_VEC3 => "vec3",
+        gl::FLOAT_VEC4 => "vec4",
+        gl::INT => "int",
+        gl::INT_VEC2 => "ivec2",
+        gl::INT_VEC3 => "ivec3",
+        gl::INT_VEC4 => "ivec4",
+        gl::UNSIGNED_INT => "uint",
+        gl::BOOL => "bool",
+        gl::FLOAT_MAT2 => "mat2",
+        gl::FLOAT_MAT3 => "mat3",
+        gl::FLOAT_MAT4 => "mat4",
+        gl::SAMPLER_2D => "sampler2D",
+        gl::SAMPLER_3D => "sampler3D",
+        gl::SAMPLER_CUBE => "samplerCube",
+        gl::SAMPLER_2D_SHADOW => "sampler2DShadow",
+        gl::SAMPLER_2D_ARRAY => "sampler2DArray",
+        _ => "unknown",
+    }
+}
+
+fn is_sampler(type_: gl::types::GLenum) -> bool {
+    matches!(
+        type_,
+        gl::SAMPLER_2D
+            | gl::SAMPLER_3D
+            | gl::SAMPLER_CUBE
+            | gl::SAMPLER_2D_SHADOW
+            | gl::SAMPLER_2D_ARRAY
+            | gl::INT_SAMPLER_2D
+            | gl::UNSIGNED_INT_SAMPLER_2D
+    )
+}
+
+unsafe fn active_variables(
+    gl: &gl::Gl,
+    program: gl::types::GLuint,
+    is_uniform: bool,
+) -> Vec<ActiveVariable> {
+    let (pname_count, pname_length) = if is_uniform {
+        (gl::ACTIVE_UNIFORMS, gl::ACTIVE_UNIFORM_MAX_LENGTH)
+    } else {
+        (gl::ACTIVE_ATTRIBUTES, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH)
+    };
+    let mut num_variable: gl::types::GLint = 0;
+    gl.GetProgramiv(program, pname_count, &mut num_variable);
+    let mut max_length: gl::types::GLint = 0;
+    gl.GetProgramiv(program, pname_length, &mut max_length);
+    let mut name = vec![0u8; max_length.max(1) as usize];
+    let mut res = Vec::<ActiveVariable>::with_capacity(num_variable.max(0) as usize);
+    for index in 0..num_variable.max(0) as gl::types::GLuint {
+        let mut length: gl::types::GLsizei = 0;
+        let mut size: gl::types::GLint = 0;
+        let mut type_: gl::types::GLenum = 0;
+        if is_uniform {
+            gl.GetActiveUniform(
+                program,
+                index,
+                name.len() as gl::types::GLsizei,
+                &mut length,
+                &mut size,
+                &mut type_,
+                name.as_mut_ptr().cast(),
+            );
+        } else {
+            gl.GetActiveAttrib(
+                program,
+                index,
+                name.len() as gl::types::GLsizei,
+                &mut length,
+                &mut size,
+                &mut type_,
+                name.as_mut_ptr().cast(),
+            );
+        }
+        let name = String::from_utf8_lossy(&name[..length.max(0) as usize]).into_owned();
+        let location = if is_uniform {
+            crate::utility::get_uniform_location(gl, &name, program)
+        } else {
+            crate::utility::get_attrib_location(gl, &name, program)
+        };
+        let name = name.strip_suffix("[0]").unwrap_or(&name).to_string();
+        res.push(ActiveVariable {
+            name,
+            type_,
+            size,
+            location,
+        });
+    }
+    res
+}
+
+/// enumerate the active uniforms. Built-in and uniform-block variables have location -1
+///
+/// # Safety
+/// The GL context of `gl` needs to be current and `program` needs to be linked.
+pub unsafe fn active_uniforms(gl: &gl::Gl, program: gl::types::GLuint) -> Vec<ActiveVariable> {
+    active_variables(gl, program, true)
+}
+
+/// enumerate the active vertex attributes
+///
+/// # Safety
+/// The GL context of `gl` needs to be current and `program` needs to be linked.
+pub unsafe fn active_attributes(gl: &gl::Gl, program: gl::types::GLuint) -> Vec<ActiveVariable> {
+    active_variables(gl, program, false)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UniformError {
+    /// the name is not an active uniform (misspelled or optimized out by the compiler)
+    UnknownName(String),
+    TypeMismatch {
+        name: String,
+        expected: gl::types::GLenum,
+        actual: &'static str,
+    },
+    /// the value has more elements than the uniform array
+    TooManyElements {
+        name: String,
+        size: gl::types::GLint,
+        count: gl::types::GLsizei,
+    },
+}
+
+impl std::fmt::Display for UniformError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UniformError::UnknownName(name) => write!(f, "no active uniform named '{}'", name),
+            UniformError::TypeMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "uniform '{}' is {} but the value is {}",
+                name,
+                type_name(*expected),
+                actual
+            ),
+            UniformError::TooManyElements { name, size, count } => write!(
+                f,
+                "uniform '{}' has {} elements but the value has {}",
+                name, size, count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UniformError {}
+
+/// value that can be set to a uniform variable
+pub trait UniformValue {
+    /// name of the value type for error messages
+    const NAME: &'static str;
+    fn is_compatible(type_: gl::types::GLenum) -> bool;
+    /// number of array elements
+    fn count(&self) -> gl::types::GLsizei {
+        1
+    }
+    /// # Safety
+    /// The program having the uniform at `location` needs to be in use.
+    unsafe fn upload(&self, gl: &gl::Gl, location: gl::types::GLint);
+}
+
+impl UniformValue for f32 {
+    const NAME: &'static str = "f32";
+    fn is_compatible(type_: gl::types::GLenum) -> bool {
+        type_ == gl::FLOAT
+    }
+    unsafe fn upload(&self, gl: &gl::Gl, location: gl::types::GLint) {
+        gl.Uniform1f(location, *self);
+    }
+}
+
+impl UniformValue for i32 {
+    const NAME: &'static str = "i32";
+    fn is_compatible(type_: gl::types::GLenum) -> bool {
+        type_ == gl::INT || type_ == gl::BOOL || is_sampler(type_)
+    }
+    unsafe fn upload(&self, gl: &gl::Gl, location: gl::types::GLint) {
+        gl.Uniform1i(location, *self);
+    }
+}
+
+impl UniformValue for u32 {
+    const NAME: &'static str = "u32";
+    fn is_compatible(type_: gl::types::GLenum) -> bool {
+        type_ == gl::UNSIGNED_INT
+    }
+    unsafe fn upload(&self, gl: &gl::Gl, location: gl::types::GLint) {
+        gl.Uniform1ui(location, *self);
+    }
+}
+
+impl UniformValue for bool {
+    const NAME: &'static str = "bool";
+    fn is_compatible(type_: gl::types::GLenum) -> bool {
+        type_ == gl::BOOL
+    }
+    unsafe fn upload(&self, gl: &gl::Gl, location: gl::types::GLint) {
+        gl.Uniform1i(location, *self as gl::types::GLint);
+    }
+}
+
+macro_rules! impl_uniform_value_for_float_array {
+    ($n: expr, $type_: expr, $name: expr, $func: ident) => {
+        impl UniformValue for [f32; $n] {
+            const NAME: &'static str = $name;
+            fn is_compatible(type_: gl::types::GLenum) -> bool {
+                type_ == $type_
+            }
+            unsafe fn upload(&self, gl: &gl::Gl, location: gl::types::GLint) {
+                gl.$func(location, 1, self.as_ptr());
+            }
+        }
+
+        impl UniformValue for [[f32; $n]] {
+            const NAME: &'static str = concat!("[", $name, "]");
+            fn is_compatible(type_: gl::types::GLenum) -> bool {
+                type_ == $type_
+            }
+            fn count(&self) -> gl::types::GLsizei {
+                self.len() as gl::types::GLsizei
+            }
+            unsafe fn upload(&self, gl: &gl::Gl, location: gl::types::GLint) {
+                gl.$func(location, self.count(), self.as_ptr().cast());
+            }
+        }
+    };
+}
+
+impl_uniform_value_for_float_array!(2, gl::FLOAT_VEC2, "[f32;2]", Uniform2fv);
+impl_uniform_value_for_float_array!(3, gl::FLOAT_VEC3, "[f32;3]", Uniform3fv);
+impl_uniform_value_for_float_array!(4, gl::FLOAT_VEC4, "[f32;4]", Uniform4fv);
+
+macro_rules! impl_uniform_value_for_matrix {
+    ($n: expr, $type_: expr, $name: expr, $func: ident) => {
+        impl UniformValue for [f32; $n] {
+            const NAME: &'static str = $name;
+            fn is_compatible(type_: gl::types::GLenum) -> bool {
+                type_ == $type_
+            }
+            unsafe fn upload(&self, gl: &gl::Gl, location: gl::types::GLint) {
+                gl.$func(location, 1, gl::FALSE, self.as_ptr());
+            }
+        }
+    };
+}
+
+// matrices are in the column major order
+impl_uniform_value_for_matrix!(9, gl::FLOAT_MAT3, "[f32;9]", UniformMatrix3fv);
+impl_uniform_value_for_matrix!(16, gl::FLOAT_MAT4, "[f32;16]", UniformMatrix4fv);
+
+impl UniformValue for [f32] {
+    const NAME: &'static str = "[f32]";
+    fn is_compatible(type_: gl::types::GLenum) -> bool {
+        type_ == gl::FLOAT
+    }
+    fn count(&self) -> gl::types::GLsizei {
+        self.len() as gl::types::GLsizei
+    }
+    unsafe fn upload(&self, gl: &gl::Gl, location: gl::types::GLint) {
+        gl.Uniform1fv(location, self.count(), self.as_ptr());
+    }
+}
+
+/// set `value` to the uniform after validating the type and the number of elements
+///
+/// # Safety
+/// The program of `uniforms` needs to be in use.
+pub unsafe fn set_uniform<T: UniformValue + ?Sized>(
+    gl: &gl::Gl,
+    uniforms: &[ActiveVariable],
+    name: &str,
+    value: &T,
+) -> Result<(), UniformError> {
+    let Some(uniform) = uniforms.iter().find(|u| u.name == name) else {
+        return Err(UniformError::UnknownName(name.to_string()));
+    };
+    if !T::is_compatible(uniform.type_) {
+        return Err(UniformError::TypeMismatch {
+            name: name.to_string(),
+            expected: uniform.type_,
+            actual: T::NAME,
+        });
+    }
+    if value.count() > uniform.size {
+        return Err(UniformError::TooManyElements {
+            name: name.to_string(),
+            size: uniform.size,
+            count: value.count(),
+        });
+    }
+    value.upload(gl, uniform.location);
+    Ok(())
+}
@@ -0,0 +1,278 @@
+//! fixed-function state of the draw call of an element buffer, such as the depth bias
+//! for the lines drawn over the triangles of the same mesh
+
+use crate::gl;
+
+/// `GL_PROGRAM_POINT_SIZE` of the desktop GL, which is missing in the GLES bindings.
+/// `gl_PointSize` is always used in GLES
+const PROGRAM_POINT_SIZE: gl::types::GLenum = 0x8642;
+
+/// blend function of the transparent elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Blend {
+    /// `src * alpha + dst * (1 - alpha)`. The elements need to be drawn from back to front
+    Alpha,
+    /// `src + dst * (1 - alpha)` for the colors premultiplied by the alpha
+    Premultiplied,
+    /// `src * alpha + dst`
+    Additive,
+    /// weighted blended order-independent transparency. `drawer_mesh` skips the elements
+    /// in `draw` and draws them in `draw_transparent` into `oit::Target`
+    WeightedOit,
+}
+
+impl Blend {
+    /// source and destination factors of `glBlendFunc`. `None` for `WeightedOit`
+    /// whose blend function is set by `oit::Target`
+    fn factors(self) -> Option<(gl::types::GLenum, gl::types::GLenum)> {
+        match self {
+            Blend::Alpha => Some((gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA)),
+            Blend::Premultiplied => Some((gl::ONE, gl::ONE_MINUS_SRC_ALPHA)),
+            Blend::Additive => Some((gl::SRC_ALPHA, gl::ONE)),
+            Blend::WeightedOit => None,
+        }
+    }
+}
+
+/// The drawers query the state of the context before drawing and restore it afterwards
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderState {
+    /// factor and units of `glPolygonOffset`. Only the triangles are offset, so push
+    /// the triangles back with positive values to draw the lines on them without flickering
+    pub polygon_offset: Option<[f32; 2]>,
+    pub depth_test: bool,
+    pub depth_write: bool,
+    /// widths other than one may not be supported in the core profile
+    pub line_width: f32,
+    /// size of the points in pixels written to `gl_PointSize`
+    pub point_size: f32,
+    /// `gl::BACK` or `gl::FRONT` to cull the faces
+    pub cull_face: Option<gl::types::GLenum>,
+    pub blend: Option<Blend>,
+}
+
+impl Default for RenderState {
+    fn default() -> Self {
+        RenderState {
+            polygon_offset: None,
+            depth_test: true,
+            depth_write: true,
+            line_width: 1.0,
+            point_size: 1.0,
+            cull_face: None,
+            blend: None,
+        }
+    }
+}
+
+impl RenderState {
+    pub fn polygon_offset(mut self, factor: f32, units: f32) -> Self {
+        self.polygon_offset = Some([factor, units]);
+        self
+    }
+
+    pub fn depth_test(mut self, depth_test: bool) -> Self {
+        self.depth_test = depth_test;
+        self
+    }
+
+    pub fn depth_write(mut self, depth_write: bool) -> Self {
+        self.depth_write = depth_write;
+        self
+    }
+
+    pub fn line_width(mut self, line_width: f32) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    pub fn point_size(mut self, point_size: f32) -> Self {
+        self.point_size = point_size;
+        self
+    }
+
+    pub fn cull_face(mut self, cull_face: Option<gl::types::GLenum>) -> Self {
+        self.cull_face = cull_face;
+        self
+    }
+
+    /// the depth write is also disabled for the blending other than `WeightedOit`
+    pub fn blend(mut self, blend: Option<Blend>) -> Self {
+        self.blend = blend;
+        if blend.is_some_and(|b| b != Blend::WeightedOit) {
+            self.depth_write = false;
+        }
+        self
+    }
+
+    /// state set for drawing. The depth write is disabled for `Blend::WeightedOit`
+    /// as `oit::Target` does
+    pub(crate) fn effective(&self) -> RenderState {
+        let mut state = *self;
+        if state.blend == Some(Blend::WeightedOit) {
+            state.depth_write = false;
+        }
+        state
+    }
+
+    /// state of the context. `point_size` is only known to be one or not, and `blend`
+    /// is `Blend::WeightedOit` for the factors other than those of `Blend`
+    pub(crate) fn current(gl: &gl::Gl, is_es: bool) -> RenderState {
+        let is_enabled = |cap: gl::types::GLenum| unsafe { gl.IsEnabled(cap) == gl::TRUE };
+        let get_float = |pname: gl::types::GLenum| {
+            let mut v = 0f32;
+            unsafe { gl.GetFloatv(pname, &mut v) };
+            v
+        };
+        let mut depth_write = gl::TRUE;
+        let mut cull_face = 0;
+        unsafe {
+            gl.GetBooleanv(gl::DEPTH_WRITEMASK, &mut depth_write);
+            gl.GetIntegerv(gl::CULL_FACE_MODE, &mut cull_face);
+        }
+        let polygon_offset = is_enabled(gl::POLYGON_OFFSET_FILL).then(|| {
+            [
+                get_float(gl::POLYGON_OFFSET_FACTOR),
+                get_float(gl::POLYGON_OFFSET_UNITS),
+            ]
+        });
+        let blend = BlendState::current(gl);
+        let blend = blend.enabled.then(|| {
+            [Blend::Alpha, Blend::Premultiplied, Blend::Additive]
+                .into_iter()
+                .find(|b| b.factors().map(|(src, dst)| [src, dst, src, dst]) == Some(blend.factors))
+                .unwrap_or(Blend::WeightedOit)
+        });
+        RenderState {
+            polygon_offset,
+            depth_test: is_enabled(gl::DEPTH_TEST),
+            depth_write: depth_write == gl::TRUE,
+            line_width: get_float(gl::LINE_WIDTH),
+            point_size: if !is_es && is_enabled(PROGRAM_POINT_SIZE) {
+                0.0
+            } else {
+                1.0
+            },
+            cull_face: is_enabled(gl::CULL_FACE).then_some(cull_face as gl::types::GLenum),
+            blend,
+        }
+    }
+
+    /// change the state from `prev` calling the functions only for the differences.
+    /// `is_es` is needed because the program point size is always enabled in GLES
+    pub(crate) fn transit(&self, gl: &gl::Gl, prev: &RenderState, is_es: bool) {
+        let enable = |cap: gl::types::GLenum, on: bool| unsafe {
+            if on {
+                gl.Enable(cap);
+            } else {
+                gl.Disable(cap);
+            }
+        };
+        if self.polygon_offset != prev.polygon_offset {
+            enable(gl::POLYGON_OFFSET_FILL, self.polygon_offset.is_some());
+            if let Some([factor, units]) = self.polygon_offset {
+                unsafe { gl.PolygonOffset(factor, units) };
+            }
+        }
+        if self.depth_test != prev.depth_test {
+            enable(gl::DEPTH_TEST, self.depth_test);
+        }
+        if self.depth_write != prev.depth_write {
+            unsafe { gl.DepthMask(self.depth_write as gl::types::GLboolean) };
+        }
+        if self.line_width != prev.line_width {
+            unsafe { gl.LineWidth(self.line_width) };
+        }
+        if !is_es && (self.point_size != 1.0) != (prev.point_size != 1.0) {
+            // otherwise the size of `glPointSize` (one by default) is used
+            enable(PROGRAM_POINT_SIZE, self.point_size != 1.0);
+        }
+        if self.blend != prev.blend {
+            // the blending of `Blend::WeightedOit` is left as it is
+            match self.blend.map(Blend::factors) {
+                None => enable(gl::BLEND, false),
+                Some(Some((src, dst))) => {
+                    enable(gl::BLEND, true);
+                    unsafe { gl.BlendFunc(src, dst) };
+                }
+                Some(None) => {}
+            }
+        }
+        if self.cull_face != prev.cull_face {
+            enable(gl::CULL_FACE, self.cull_face.is_some());
+            if let Some(mode) = self.cull_face {
+                unsafe { gl.CullFace(mode) };
+            }
+        }
+    }
+}
+
+/// blend enable and factors of the context to be restored exactly, which `RenderState`
+/// cannot represent for the factors set by others (e.g., `oit::Target`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BlendState {
+    pub enabled: bool,
+    /// source and destination of the color, and those of the alpha
+    pub factors: [gl::types::GLenum; 4],
+}
+
+impl BlendState {
+    pub(crate) fn current(gl: &gl::Gl) -> BlendState {
+        let mut factors = [0 as gl::types::GLint; 4];
+        let pnames = [
+            gl::BLEND_SRC_RGB,
+            gl::BLEND_DST_RGB,
+            gl::BLEND_SRC_ALPHA,
+            gl::BLEND_DST_ALPHA,
+        ];
+        let enabled = unsafe {
+            for (factor, pname) in factors.iter_mut().zip(pnames) {
+                gl.GetIntegerv(pname, factor);
+            }
+            gl.IsEnabled(gl::BLEND) == gl::TRUE
+        };
+        BlendState {
+            enabled,
+            factors: factors.map(|f| f as gl::types::GLenum),
+        }
+    }
+
+    pub(crate) fn restore(&self, gl: &gl::Gl) {
+        let [src_rgb, dst_rgb, src_alpha, dst_alpha] = self.factors;
+        unsafe {
+            if self.enabled {
+                gl.Enable(gl::BLEND);
+            } else {
+                gl.Disable(gl::BLEND);
+            }
+            gl.BlendFuncSeparate(src_rgb, dst_rgb, src_alpha, dst_alpha);
+        }
+    }
+}
+
+/// state of the context saved by the drawers before drawing their elements
+pub(crate) struct SavedState {
+    pub state: RenderState,
+    pub blend: BlendState,
+}
+
+impl SavedState {
+    pub(crate) fn query(gl: &gl::Gl, is_es: bool) -> SavedState {
+        SavedState {
+            state: RenderState::current(gl, is_es),
+            blend: BlendState::current(gl),
+        }
+    }
+
+    /// change the state from `current` (that of the last element drawn) back to the saved one
+    pub(crate) fn restore(&self, gl: &gl::Gl, current: &RenderState, is_es: bool) {
+        let state = RenderState {
+            blend: current.blend,
+            ..self.state
+        };
+        state.transit(gl, current, is_es);
+        if current.blend != self.state.blend {
+            self.blend.restore(gl);
+        }
+    }
+}
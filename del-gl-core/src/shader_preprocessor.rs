@@ -0,0 +1,524 @@
+//! small GLSL preprocessor resolving `#include "name"` from a registry of snippets,
+//! injecting `#define`s and putting the `#version` line.
+//!
+//! The sources are written in the GLSL 3.30 style (`in`/`out`, `layout(location = N)`,
+//! `texture()` and a user-declared fragment output) and are translated to the
+//! dialect supported by the context (see `GlslDialect`).
+
+use crate::gl;
+
+/// GLSL dialect the shaders are translated to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlslDialect {
+    /// GLSL ES 1.00 (OpenGL ES 2.0)
+    Es100,
+    /// GLSL 1.20 (legacy OpenGL 2.1 context)
+    Glsl120,
+    /// GLSL ES 3.00 (OpenGL ES 3.x)
+    Es300,
+    /// GLSL 3.30 (OpenGL 3.3 or later)
+    Glsl330,
+}
+
+impl GlslDialect {
+    /// parse the string of `gl::SHADING_LANGUAGE_VERSION` such as
+    /// `4.60 NVIDIA`, `1.20` or `OpenGL ES GLSL ES 3.20`
+    pub fn from_version_string(s: &str) -> Option<Self> {
+        let is_es = s.contains(" ES");
+        let s = &s[s.find(|c: char| c.is_ascii_digit())?..];
+        let s: String = s
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+        let mut it = s.split('.');
+        let major: u32 = it.next()?.parse().ok()?;
+        let minor = it.next().unwrap_or("0");
+        // "3.3" and "3.30" are the same
+        let minor: u32 = format!("{:0<2}", minor).get(..2)?.parse().ok()?;
+        let version = major * 100 + minor;
+        let dialect = match (is_es, version >= 300, version >= 330) {
+            (true, true, _) => GlslDialect::Es300,
+            (true, false, _) => GlslDialect::Es100,
+            (false, _, true) => GlslDialect::Glsl330,
+            (false, _, false) => GlslDialect::Glsl120,
+        };
+        Some(dialect)
+    }
+
+    /// dialect of the current context. `Glsl330` if the version cannot be queried
+    pub fn detect(gl: &gl::Gl) -> Self {
+        crate::get_gl_string(gl, gl::SHADING_LANGUAGE_VERSION)
+            .and_then(|s| Self::from_version_string(&s.to_string_lossy()))
+            .unwrap_or(GlslDialect::Glsl330)
+    }
+
+    /// the argument of the `#version` line
+    pub fn version(&self) -> &'static str {
+        match self {
+            GlslDialect::Es100 => "100",
+            GlslDialect::Glsl120 => "120",
+            GlslDialect::Es300 => "300 es",
+            GlslDialect::Glsl330 => "330",
+        }
+    }
+
+    pub fn is_es(&self) -> bool {
+        matches!(self, GlslDialect::Es100 | GlslDialect::Es300)
+    }
+
+    /// `attribute`/`varying` and `gl_FragColor` instead of `in`/`out`
+    pub fn is_legacy(&self) -> bool {
+        matches!(self, GlslDialect::Es100 | GlslDialect::Glsl120)
+    }
+
+    /// geometry shaders are available in GLSL 3.30 but not in GLSL ES 3.00
+    pub fn has_geometry_shader(&self) -> bool {
+        matches!(self, GlslDialect::Glsl330)
+    }
+}
+
+/// replace the identifier `from` with `to` if it is not a part of a longer identifier
+fn replace_identifier(line: &str, from: &str, to: &str) -> String {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut res = String::new();
+    let mut rest = line;
+    while let Some(i) = rest.find(from) {
+        let before = rest[..i].chars().next_back();
+        let after = rest[i + from.len()..].chars().next();
+        res += &rest[..i];
+        if before.is_some_and(is_ident) || after.is_some_and(is_ident) {
+            res += from;
+        } else {
+            res += to;
+        }
+        rest = &rest[i + from.len()..];
+    }
+    res += rest;
+    res
+}
+
+/// split a line into the code (`true`) and the comments (`false`).
+/// `in_block` tells whether the line starts and ends in a block comment
+fn split_comments<'a>(line: &'a str, in_block: &mut bool) -> Vec<(bool, &'a str)> {
+    let mut res = vec![];
+    let mut rest = line;
+    while !rest.is_empty() {
+        if *in_block {
+            let Some(i) = rest.find("*/") else {
+                res.push((false, rest));
+                break;
+            };
+            res.push((false, &rest[..i + 2]));
+            rest = &rest[i + 2..];
+            *in_block = false;
+            continue;
+        }
+        let Some(i) = rest.find("/*").into_iter().chain(rest.find("//")).min() else {
+            res.push((true, rest));
+            break;
+        };
+        res.push((true, &rest[..i]));
+        if rest[i..].starts_with("//") {
+            res.push((false, &rest[i..]));
+            break;
+        }
+        res.push((false, "/*"));
+        rest = &rest[i + 2..];
+        *in_block = true;
+    }
+    res
+}
+
+/// translate a declaration `[layout(...)] in|out ...` at the global scope
+/// of GLSL 3.30 style to GLSL 1.00/1.20. `None` for the other lines
+fn translate_declaration_to_legacy(stage: crate::ShaderStage, code: &str) -> Option<String> {
+    let indent = &code[..code.len() - code.trim_start().len()];
+    let mut decl = code.trim_start();
+    // the locations are lost. The attributes need to be looked up by name
+    if let Some(rest) = decl.strip_prefix("layout") {
+        if rest.trim_start().starts_with('(') {
+            decl = rest[rest.find(')')? + 1..].trim_start();
+        }
+    }
+    let qualifier_end = decl.find(char::is_whitespace)?;
+    let (qualifier, rest) = (&decl[..qualifier_end], decl[qualifier_end..].trim_start());
+    let res = match (qualifier, stage) {
+        ("in", crate::ShaderStage::Vertex) => format!("{}attribute {}", indent, rest),
+        ("in", _) | ("out", crate::ShaderStage::Vertex) => format!("{}varying {}", indent, rest),
+        ("out", _) => {
+            // `out vec4 FragColor;` becomes an alias of `gl_FragColor`
+            let name = rest.trim().trim_end_matches(';').split_whitespace().last();
+            format!("#define {} gl_FragColor", name.unwrap_or("FragColor"))
+        }
+        _ => return None,
+    };
+    Some(res)
+}
+
+/// translate GLSL 3.30 style source to GLSL 1.00/1.20. Only the tokens
+/// outside the comments are translated
+fn translate_to_legacy(stage: crate::ShaderStage, src: &str) -> String {
+    let mut res = String::new();
+    let mut in_block = false;
+    // nesting of the braces and parentheses. The declarations are only at the global scope
+    let mut depth = 0i32;
+    for line in src.lines() {
+        if !in_block && line.trim_start().starts_with('#') {
+            res += line;
+            res += "\n";
+            continue;
+        }
+        let starts_in_code = !in_block;
+        let segments = split_comments(line, &mut in_block);
+        let decl = match segments.first() {
+            Some(&(true, code)) if starts_in_code && depth == 0 => {
+                translate_declaration_to_legacy(stage, code)
+            }
+            _ => None,
+        };
+        match decl {
+            Some(decl) => {
+                res += &decl;
+                // the comments after the declaration, which is on one line
+                let comments: String = segments[1..]
+                    .iter()
+                    .filter(|(is_code, _)| !is_code)
+                    .map(|(_, text)| *text)
+                    .collect();
+                if !comments.is_empty() && !res.ends_with(char::is_whitespace) {
+                    res += " ";
+                }
+                res += &comments;
+            }
+            None => {
+                for &(is_code, text) in &segments {
+                    if is_code {
+                        res += &replace_identifier(text, "texture", "texture2D");
+                    } else {
+                        res += text;
+                    }
+                }
+            }
+        }
+        for &(_, code) in segments.iter().filter(|(is_code, _)| *is_code) {
+            for c in code.chars() {
+                match c {
+                    '{' | '(' => depth += 1,
+                    '}' | ')' => depth -= 1,
+                    _ => {}
+                }
+            }
+        }
+        res += "\n";
+    }
+    res
+}
+
+/// `#line` directive numbering the next line as the `line`-th line of the source string
+/// `string`. `offset` is one for the versions before GLSL 3.30 (see `line_directive_offset`)
+fn line_directive(line: usize, string: usize, offset: usize) -> String {
+    format!("#line {} {}\n", line - offset, string)
+}
+
+/// one if `#line N` numbers the next line `N + 1` in the GLSL `version` (e.g., `"120"`
+/// or `"300 es"`) as before GLSL 3.30 and in GLSL ES 1.00, and zero otherwise
+pub(crate) fn line_directive_offset(version: &str) -> usize {
+    let mut tokens = version.split_whitespace();
+    let number: u32 = match tokens.next().and_then(|v| v.parse().ok()) {
+        Some(number) => number,
+        None => return 0,
+    };
+    let is_es = tokens.next() == Some("es") || number == 100;
+    usize::from(if is_es { number < 300 } else { number < 330 })
+}
+
+/// flip the z coordinate of the clip space. Same as multiplying `[z flip]` to the projection
+const SNIPPET_Z_FLIP: &str = "
+vec4 z_flip(vec4 p) {
+    return vec4(p.x, p.y, -p.z, p.w);
+}
+";
+
+/// transformation from the model coordinate to the clip coordinate.
+/// define `Z_FLIP` to flip the z coordinate after the projection
+const SNIPPET_TRANSFORM: &str = "
+#include \"z_flip\"
+
+uniform mat4 matMV;
+uniform mat4 matPrj;
+
+vec4 transform(vec3 pos) {
+#ifdef Z_FLIP
+    return z_flip(matPrj * matMV * vec4(pos, 1.0));
+#else
+    return matPrj * matMV * vec4(pos, 1.0);
+#endif
+}
+";
+
+/// Blinn-Phong reflection. All the vectors are in the view coordinate
+const SNIPPET_LIGHTING: &str = "
+vec3 blinn_phong(
+    vec3 nrm,
+    vec3 light_dir,
+    vec3 diffuse,
+    vec3 specular,
+    float shininess)
+{
+    vec3 n = normalize(nrm);
+    vec3 l = normalize(light_dir);
+    vec3 h = normalize(l + vec3(0.0, 0.0, 1.0));
+    float d = max(dot(n, l), 0.0);
+    float s = (d > 0.0) ? pow(max(dot(n, h), 0.0), shininess) : 0.0;
+    return d * diffuse + s * specular;
+}
+
+/// normal of the face toward the camera from the screen-space derivatives
+/// of the position in the view coordinate (flat shading)
+vec3 face_normal(vec3 pos) {
+    return normalize(cross(dFdx(pos), dFdy(pos)));
+}
+";
+
+/// linear interpolation of the color table. `NCOLOR` needs to be defined
+/// and the `colors` needs to be set as a uniform array
+const SNIPPET_COLORMAP: &str = "
+uniform vec3 colors[NCOLOR];
+
+vec3 colormap(float val, float val_min, float val_max) {
+    float scaled_value = clamp((val - val_min) / (val_max - val_min), 0.0, 1.0) * float(NCOLOR - 1);
+    int idx_color = int(min(scaled_value, float(NCOLOR - 2)));
+    float r01 = scaled_value - float(idx_color);
+    vec3 clr = colors[0];
+    // loop with the constant bound so that the array is indexed only by the loop index
+    for (int i = 0; i < NCOLOR - 1; ++i) {
+        if (i == idx_color) {
+            clr = (1.0 - r01) * colors[i] + r01 * colors[i + 1];
+        }
+    }
+    return clr;
+}
+";
+
+/// vertex shader shared by the mesh drawers. define `VTX_VALUE` to pass a scalar value
+/// per vertex, or `VTX_TEX` to pass a texture coordinate per vertex.
+/// define `VTX_NORMAL` to pass the normal and `VTX_POS_VIEW` to pass the position
+/// in the view coordinate. define `VTX_COLOR` to pass an RGBA color per vertex, and
+/// `POINT_SIZE` to set `gl_PointSize` from the uniform
+const SNIPPET_MESH_VERTEX: &str = "
+#include \"transform\"
+
+layout (location = 0) in vec3 position;
+#ifdef VTX_VALUE
+layout (location = 1) in float value;
+out float val;
+#endif
+#ifdef VTX_TEX
+layout (location = 1) in vec2 texIn;
+out vec2 texPrj;
+#endif
+#ifdef VTX_NORMAL
+layout (location = 2) in vec3 normal;
+out vec3 nrm;
+#endif
+#ifdef VTX_POS_VIEW
+out vec3 pos_view;
+#endif
+#ifdef VTX_COLOR
+layout (location = 3) in vec4 vtx_color;
+out vec4 clr_vtx;
+#endif
+#ifdef POINT_SIZE
+uniform float point_size;
+#endif
+
+void main() {
+    gl_Position = transform(position);
+#ifdef POINT_SIZE
+    gl_PointSize = point_size;
+#endif
+#ifdef VTX_VALUE
+    val = value;
+#endif
+#ifdef VTX_TEX
+    texPrj = texIn;
+#endif
+#ifdef VTX_NORMAL
+    // the model-view matrix is assumed to have no non-uniform scaling
+    nrm = (matMV * vec4(normal, 0.0)).xyz;
+#endif
+#ifdef VTX_POS_VIEW
+    pos_view = (matMV * vec4(position, 1.0)).xyz;
+#endif
+#ifdef VTX_COLOR
+    clr_vtx = vtx_color;
+#endif
+}
+";
+
+const BUILTIN_SNIPPETS: &[(&str, &str)] = &[
+    ("z_flip", SNIPPET_Z_FLIP),
+    ("transform", SNIPPET_TRANSFORM),
+    ("lighting", SNIPPET_LIGHTING),
+    ("colormap", SNIPPET_COLORMAP),
+    ("mesh_vertex", SNIPPET_MESH_VERTEX),
+];
+
+#[derive(Clone)]
+pub struct Preprocessor {
+    defines: Vec<(String, String)>,
+    snippets: Vec<(String, String)>,
+}
+
+impl Preprocessor {
+    pub fn new() -> Self {
+        Preprocessor {
+            defines: vec![],
+            snippets: vec![],
+        }
+    }
+
+    /// add `#define name value`. The value of the same name is overwritten
+    pub fn define<T: ToString>(&mut self, name: &str, value: T) {
+        let value = value.to_string();
+        match self.defines.iter_mut().find(|(n, _)| n == name) {
+            Some(define) => define.1 = value,
+            None => self.defines.push((name.to_string(), value)),
+        }
+    }
+
+    pub fn undefine(&mut self, name: &str) {
+        self.defines.retain(|(n, _)| n != name);
+    }
+
+    pub fn is_defined(&self, name: &str) -> bool {
+        self.defines.iter().any(|(n, _)| n == name)
+    }
+
+    /// register a snippet for `#include "name"`. A built-in snippet of the same name is hidden
+    pub fn register_snippet(&mut self, name: &str, src: &str) {
+        self.snippets.retain(|(n, _)| n != name);
+        self.snippets.push((name.to_string(), src.to_string()));
+    }
+
+    fn snippet(&self, name: &str) -> Option<&str> {
+        self.snippets
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, src)| src.as_str())
+            .or_else(|| {
+                BUILTIN_SNIPPETS
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, src)| *src)
+            })
+    }
+
+    /// replace the `#include` lines of the source string `string` by the snippets.
+    /// The snippets are numbered as the source strings from one in the order of inclusion
+    fn expand_includes(
+        &self,
+        stage: crate::ShaderStage,
+        (src, string): (&str, usize),
+        included: &mut Vec<String>,
+        line_offset: usize,
+        res: &mut String,
+    ) -> Result<(), crate::ShaderError> {
+        for (iline, line) in src.lines().enumerate() {
+            let Some(arg) = line.trim_start().strip_prefix("#include") else {
+                *res += line;
+                *res += "\n";
+                continue;
+            };
+            let arg = arg.trim();
+            let name = arg
+                .strip_prefix('"')
+                .and_then(|s| s.strip_suffix('"'))
+                .or_else(|| arg.strip_prefix('<').and_then(|s| s.strip_suffix('>')));
+            let Some(name) = name else {
+                return Err(crate::ShaderError {
+                    stage,
+                    log: format!("malformed #include at line {}: {}", iline + 1, line),
+                    annotated_source: String::new(),
+                });
+            };
+            if included.iter().any(|n| n == name) {
+                // each snippet is included only once
+                *res += "\n";
+                continue;
+            }
+            let Some(snippet) = self.snippet(name) else {
+                return Err(crate::ShaderError {
+                    stage,
+                    log: format!(
+                        "unknown snippet \"{}\" included at line {}",
+                        name,
+                        iline + 1
+                    ),
+                    annotated_source: String::new(),
+                });
+            };
+            included.push(name.to_string());
+            *res += &line_directive(1, included.len(), line_offset);
+            self.expand_includes(stage, (snippet, included.len()), included, line_offset, res)?;
+            *res += &line_directive(iline + 2, string, line_offset);
+        }
+        Ok(())
+    }
+
+    /// expand the source for the `stage` and translate it to the `dialect`.
+    /// A source having its own `#version` line is not translated.
+    /// The `#line` directives keep the line numbers of the source and the snippets
+    /// in the info log of the compiler
+    pub fn process(
+        &self,
+        stage: crate::ShaderStage,
+        src: &str,
+        dialect: GlslDialect,
+    ) -> Result<String, crate::ShaderError> {
+        let mut version = None;
+        let mut body = String::new();
+        for line in src.lines() {
+            match line.trim_start().strip_prefix("#version") {
+                Some(v) => version = Some(v.trim().to_string()),
+                None => body += line,
+            }
+            body += "\n";
+        }
+        let version_arg = version.as_deref().unwrap_or(dialect.version());
+        let line_offset = line_directive_offset(version_arg);
+        let mut expanded = String::new();
+        self.expand_includes(stage, (&body, 0), &mut vec![], line_offset, &mut expanded)?;
+        let mut res = format!("#version {}\n", version_arg);
+        if version.is_none()
+            && dialect == GlslDialect::Es100
+            && stage == crate::ShaderStage::Fragment
+        {
+            // `dFdx` and `dFdy` are built-in in the other dialects
+            res += "#extension GL_OES_standard_derivatives : enable\n";
+        }
+        if version.is_none() && dialect.is_es() && stage == crate::ShaderStage::Fragment {
+            res += "#ifdef GL_FRAGMENT_PRECISION_HIGH\n";
+            res += "precision highp float;\n";
+            res += "#else\n";
+            res += "precision mediump float;\n";
+            res += "#endif\n";
+        }
+        for (name, value) in &self.defines {
+            res += &format!("#define {} {}\n", name, value);
+        }
+        res += &line_directive(1, 0, line_offset);
+        if version.is_some() || !dialect.is_legacy() {
+            res += &expanded;
+        } else {
+            res += &translate_to_legacy(stage, &expanded);
+        }
+        Ok(res)
+    }
+}
+
+impl Default for Preprocessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,348 @@
+//! shader program compiled either from embedded sources or from files.
+//! The program compiled from files is recompiled when the files are modified,
+//! so that the shaders can be edited while the application is running.
+
+use crate::gl;
+use std::rc::Rc;
+
+pub enum ShaderSource {
+    Embedded(String),
+    File {
+        path: std::path::PathBuf,
+        modified: Option<std::time::SystemTime>,
+    },
+}
+
+impl ShaderSource {
+    /// source baked in the binary. The trailing null character is removed if exists.
+    pub fn from_bytes(src: &[u8]) -> Self {
+        let len = src.iter().position(|&c| c == 0).unwrap_or(src.len());
+        ShaderSource::Embedded(String::from_utf8_lossy(&src[..len]).into_owned())
+    }
+
+    /// source loaded from the file at `path`, which is watched for modification
+    pub fn from_path<P: Into<std::path::PathBuf>>(path: P) -> Self {
+        ShaderSource::File {
+            path: path.into(),
+            modified: None,
+        }
+    }
+
+    fn modified_time(path: &std::path::Path) -> Option<std::time::SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    fn is_modified(&self) -> bool {
+        match self {
+            ShaderSource::Embedded(_) => false,
+            ShaderSource::File { path, modified } => {
+                let time = Self::modified_time(path);
+                time.is_some() && time != *modified
+            }
+        }
+    }
+
+    fn read(&mut self, stage: crate::ShaderStage) -> Result<String, crate::ShaderError> {
+        match self {
+            ShaderSource::Embedded(src) => Ok(src.clone()),
+            ShaderSource::File { path, modified } => {
+                // the time is updated even if reading fails so the error is reported only once
+                *modified = Self::modified_time(path);
+                std::fs::read_to_string(&*path).map_err(|e| crate::ShaderError {
+                    stage,
+                    log: format!("failed to read {}: {}", path.display(), e),
+                    annotated_source: String::new(),
+                })
+            }
+        }
+    }
+}
+
+/// owned program object deleted on drop
+pub struct Program {
+    gl: Rc<gl::Gl>,
+    id: gl::types::GLuint,
+}
+
+impl Program {
+    /// take the ownership of the linked program `id`
+    pub fn from_raw(gl: &Rc<gl::Gl>, id: gl::types::GLuint) -> Self {
+        Program {
+            gl: Rc::clone(gl),
+            id,
+        }
+    }
+
+    pub fn id(&self) -> gl::types::GLuint {
+        self.id
+    }
+
+    pub fn use_program(&self) {
+        unsafe {
+            self.gl.UseProgram(self.id);
+        }
+    }
+}
+
+impl Drop for Program {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteProgram(self.id);
+        }
+    }
+}
+
+pub struct ShaderProgram {
+    /// `None` until the first successful compilation
+    program: Option<Program>,
+    vertex: ShaderSource,
+    /// optional stage between the vertex and fragment shaders
+    geometry: Option<ShaderSource>,
+    fragment: ShaderSource,
+    /// `#include` and `#define` are resolved by this preprocessor
+    pub preprocessor: crate::shader_preprocessor::Preprocessor,
+    /// dialect of the shaders. `None` selects it from the version of the context
+    pub dialect: Option<crate::shader_preprocessor::GlslDialect>,
+    /// dialect of the linked program
+    compiled_dialect: Option<crate::shader_preprocessor::GlslDialect>,
+    uniforms: Vec<crate::reflection::ActiveVariable>,
+    attributes: Vec<crate::reflection::ActiveVariable>,
+    /// names of the uniforms declared in the sources, including the ones inactive
+    /// in the current variant
+    declared_uniforms: Vec<String>,
+    /// unknown names already reported by `set_if_active`
+    reported_names: std::cell::RefCell<Vec<String>>,
+    pub poll_interval: std::time::Duration,
+    last_poll: Option<std::time::Instant>,
+}
+
+impl ShaderProgram {
+    pub fn new(vertex: ShaderSource, fragment: ShaderSource) -> Self {
+        ShaderProgram {
+            program: None,
+            vertex,
+            geometry: None,
+            fragment,
+            preprocessor: crate::shader_preprocessor::Preprocessor::new(),
+            dialect: None,
+            compiled_dialect: None,
+            uniforms: vec![],
+            attributes: vec![],
+            declared_uniforms: vec![],
+            reported_names: std::cell::RefCell::new(vec![]),
+            poll_interval: std::time::Duration::from_millis(500),
+            last_poll: None,
+        }
+    }
+
+    pub fn set_sources(&mut self, vertex: ShaderSource, fragment: ShaderSource) {
+        self.vertex = vertex;
+        self.fragment = fragment;
+    }
+
+    /// add a geometry shader to the program. Only available in the `Glsl330` dialect
+    pub fn with_geometry(mut self, geometry: ShaderSource) -> Self {
+        self.geometry = Some(geometry);
+        self
+    }
+
+    /// replace the geometry shader, or remove it with `None`
+    pub fn set_geometry(&mut self, geometry: Option<ShaderSource>) {
+        self.geometry = geometry;
+    }
+
+    /// compile and link the program. If it fails, the previously linked program is kept.
+    pub fn compile(&mut self, gl: &Rc<gl::Gl>) -> Result<(), crate::ShaderError> {
+        use crate::ShaderStage;
+        let dialect = self
+            .dialect
+            .unwrap_or_else(|| crate::shader_preprocessor::GlslDialect::detect(gl));
+        let src_vertex = self.vertex.read(ShaderStage::Vertex)?;
+        let src_vertex = self
+            .preprocessor
+            .process(ShaderStage::Vertex, &src_vertex, dialect)?;
+        let src_fragment = self.fragment.read(ShaderStage::Fragment)?;
+        let src_fragment =
+            self.preprocessor
+                .process(ShaderStage::Fragment, &src_fragment, dialect)?;
+        let mut uniform_names = declared_uniforms(&src_vertex);
+        uniform_names.extend(declared_uniforms(&src_fragment));
+        let id = match &mut self.geometry {
+            None => unsafe {
+                crate::utility::compile_shaders(gl, src_vertex.as_bytes(), src_fragment.as_bytes())?
+            },
+            Some(geometry) => {
+                if !dialect.has_geometry_shader() {
+                    return Err(crate::ShaderError {
+                        stage: ShaderStage::Geometry,
+                        log: format!("geometry shader is not supported in {:?}", dialect),
+                        annotated_source: String::new(),
+                    });
+                }
+                let src_geometry = geometry.read(ShaderStage::Geometry)?;
+                let src_geometry =
+                    self.preprocessor
+                        .process(ShaderStage::Geometry, &src_geometry, dialect)?;
+                uniform_names.extend(declared_uniforms(&src_geometry));
+                unsafe {
+                    crate::utility::compile_shaders_with_geometry(
+                        gl,
+                        src_vertex.as_bytes(),
+                        src_geometry.as_bytes(),
+                        src_fragment.as_bytes(),
+                    )?
+                }
+            }
+        };
+        // the previous program is deleted here
+        self.program = Some(Program::from_raw(gl, id));
+        self.compiled_dialect = Some(dialect);
+        self.declared_uniforms = uniform_names;
+        unsafe {
+            self.uniforms = crate::reflection::active_uniforms(gl, id);
+            self.attributes = crate::reflection::active_attributes(gl, id);
+        }
+        Ok(())
+    }
+
+    /// recompile the program if any of the source files is modified, e.g., every frame
+    /// while editing the shaders. The files are polled at most once per `poll_interval`.
+    /// Returns `Ok(true)` if the program is replaced. The previous program is kept
+    /// if the compilation fails
+    pub fn reload_if_changed(&mut self, gl: &Rc<gl::Gl>) -> Result<bool, crate::ShaderError> {
+        let now = std::time::Instant::now();
+        if let Some(last_poll) = self.last_poll {
+            if now.duration_since(last_poll) < self.poll_interval {
+                return Ok(false);
+            }
+        }
+        self.last_poll = Some(now);
+        let geometry_modified = self.geometry.as_ref().is_some_and(|g| g.is_modified());
+        if !self.vertex.is_modified() && !self.fragment.is_modified() && !geometry_modified {
+            return Ok(false);
+        }
+        self.compile(gl)?;
+        Ok(true)
+    }
+
+    /// name of the program object. zero until the first successful compilation
+    pub fn id(&self) -> gl::types::GLuint {
+        self.program.as_ref().map_or(0, |p| p.id())
+    }
+
+    /// dialect the linked program is translated to. `None` until the first successful compilation
+    pub fn compiled_dialect(&self) -> Option<crate::shader_preprocessor::GlslDialect> {
+        self.compiled_dialect
+    }
+
+    /// active uniforms of the linked program
+    pub fn uniforms(&self) -> &[crate::reflection::ActiveVariable] {
+        &self.uniforms
+    }
+
+    /// active vertex attributes of the linked program
+    pub fn attributes(&self) -> &[crate::reflection::ActiveVariable] {
+        &self.attributes
+    }
+
+    /// location of the active uniform. -1 if not found
+    pub fn uniform_location(&self, name: &str) -> gl::types::GLint {
+        self.uniforms
+            .iter()
+            .find(|u| u.name == name)
+            .map_or(-1, |u| u.location)
+    }
+
+    /// location of the active vertex attribute. -1 if not found
+    pub fn attrib_location(&self, name: &str) -> gl::types::GLint {
+        self.attributes
+            .iter()
+            .find(|a| a.name == name)
+            .map_or(-1, |a| a.location)
+    }
+
+    pub fn use_program(&self, gl: &gl::Gl) {
+        unsafe {
+            gl.UseProgram(self.id());
+        }
+    }
+
+    /// set the value to the uniform variable after validating its name and type.
+    /// The program needs to be in use.
+    pub fn set<T: crate::reflection::UniformValue + ?Sized>(
+        &self,
+        gl: &gl::Gl,
+        name: &str,
+        value: &T,
+    ) -> Result<(), crate::reflection::UniformError> {
+        unsafe { crate::reflection::set_uniform(gl, &self.uniforms, name, value) }
+    }
+
+    /// check if the uniform `name` is declared in the sources of the compiled program.
+    /// A declared uniform may be inactive because it is removed by a `#define` or
+    /// optimized out by the driver
+    pub fn declares_uniform(&self, name: &str) -> bool {
+        self.declared_uniforms.iter().any(|n| n == name)
+    }
+
+    /// same as `set` but a declared uniform that is not active is skipped. This is used by
+    /// the drawers because uniforms unused in a shader variant (or in a reloaded shader)
+    /// are optimized out. A name not declared at all (e.g., a typo) is warned once.
+    /// The other errors are logged. They are bugs of the drawer and fail the debug builds
+    /// unless the shaders are loaded from the files, which may be under editing
+    pub(crate) fn set_if_active<T: crate::reflection::UniformValue + ?Sized>(
+        &self,
+        gl: &gl::Gl,
+        name: &str,
+        value: &T,
+    ) {
+        match self.set(gl, name, value) {
+            Ok(()) => {}
+            Err(crate::reflection::UniformError::UnknownName(_)) => {
+                if self.declares_uniform(name) {
+                    return;
+                }
+                let mut reported = self.reported_names.borrow_mut();
+                if !reported.iter().any(|n| n == name) {
+                    debug_assert!(self.has_file_source(), "uniform '{}' is not declared", name);
+                    log::warn!("uniform '{}' is not declared in the shaders", name);
+                    reported.push(name.to_string());
+                }
+            }
+            Err(e) => {
+                debug_assert!(self.has_file_source(), "{}", e);
+                log::error!("{}", e);
+            }
+        }
+    }
+
+    fn has_file_source(&self) -> bool {
+        let is_file = |s: &ShaderSource| matches!(s, ShaderSource::File { .. });
+        is_file(&self.vertex)
+            || is_file(&self.fragment)
+            || self.geometry.as_ref().is_some_and(is_file)
+    }
+
+    /// delete the program object. It is also deleted on drop
+    pub fn delete(&mut self) {
+        self.program = None;
+        self.compiled_dialect = None;
+        self.uniforms.clear();
+        self.attributes.clear();
+        self.declared_uniforms.clear();
+    }
+}
+
+/// names of the uniforms declared in the preprocessed `src` regardless of `#ifdef`.
+/// The `[n]` of the arrays is removed
+fn declared_uniforms(src: &str) -> Vec<String> {
+    src.lines()
+        .filter_map(|line| {
+            let decl = line.trim().strip_prefix("uniform ")?;
+            let decl = decl.split(';').next()?;
+            let name = decl.split_whitespace().last()?;
+            let name = name.split('[').next()?;
+            Some(name.to_string())
+        })
+        .collect()
+}
@@ -0,0 +1,469 @@
+//! owned texture object deleted on drop, and the description of its storage and sampler
+
+use crate::gl;
+use std::rc::Rc;
+
+/// storage format of the texels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFormat {
+    R8,
+    Rg8,
+    Rgb8,
+    Rgba8,
+    /// sRGB-encoded RGB8. The shader reads linear values
+    Srgb8,
+    /// sRGB-encoded RGBA8 with linear alpha
+    Srgb8Alpha8,
+    R32F,
+    Rgb32F,
+    Rgba16F,
+    Rgba32F,
+    Depth24,
+    Depth32F,
+    Depth24Stencil8,
+}
+
+impl TextureFormat {
+    /// (internal format, format, type) for `TexImage2D`
+    pub fn to_gl(self) -> (gl::types::GLint, gl::types::GLenum, gl::types::GLenum) {
+        let (internal, format, type_) = match self {
+            TextureFormat::R8 => (gl::R8, gl::RED, gl::UNSIGNED_BYTE),
+            TextureFormat::Rg8 => (gl::RG8, gl::RG, gl::UNSIGNED_BYTE),
+            TextureFormat::Rgb8 => (gl::RGB8, gl::RGB, gl::UNSIGNED_BYTE),
+            TextureFormat::Rgba8 => (gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE),
+            TextureFormat::Srgb8 => (gl::SRGB8, gl::RGB, gl::UNSIGNED_BYTE),
+            TextureFormat::Srgb8Alpha8 => (gl::SRGB8_ALPHA8, gl::RGBA, gl::UNSIGNED_BYTE),
+            TextureFormat::R32F => (gl::R32F, gl::RED, gl::FLOAT),
+            TextureFormat::Rgb32F => (gl::RGB32F, gl::RGB, gl::FLOAT),
+            TextureFormat::Rgba16F => (gl::RGBA16F, gl::RGBA, gl::HALF_FLOAT),
+            TextureFormat::Rgba32F => (gl::RGBA32F, gl::RGBA, gl::FLOAT),
+            TextureFormat::Depth24 => {
+                (gl::DEPTH_COMPONENT24, gl::DEPTH_COMPONENT, gl::UNSIGNED_INT)
+            }
+            TextureFormat::Depth32F => (gl::DEPTH_COMPONENT32F, gl::DEPTH_COMPONENT, gl::FLOAT),
+            TextureFormat::Depth24Stencil8 => (
+                gl::DEPTH24_STENCIL8,
+                gl::DEPTH_STENCIL,
+                gl::UNSIGNED_INT_24_8,
+            ),
+        };
+        (internal as gl::types::GLint, format, type_)
+    }
+
+    /// size of a texel of the client data in bytes
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            TextureFormat::R8 => 1,
+            TextureFormat::Rg8 => 2,
+            TextureFormat::Rgb8 | TextureFormat::Srgb8 => 3,
+            TextureFormat::Rgba8 | TextureFormat::Srgb8Alpha8 => 4,
+            TextureFormat::R32F => 4,
+            TextureFormat::Rgb32F => 12,
+            TextureFormat::Rgba16F => 8,
+            TextureFormat::Rgba32F => 16,
+            TextureFormat::Depth24 | TextureFormat::Depth32F | TextureFormat::Depth24Stencil8 => 4,
+        }
+    }
+
+    pub fn is_depth(self) -> bool {
+        matches!(
+            self,
+            TextureFormat::Depth24 | TextureFormat::Depth32F | TextureFormat::Depth24Stencil8
+        )
+    }
+
+    /// the sRGB-encoded counterpart of `Rgb8` and `Rgba8`. Other formats are returned as is
+    pub fn srgb(self) -> Self {
+        match self {
+            TextureFormat::Rgb8 => TextureFormat::Srgb8,
+            TextureFormat::Rgba8 => TextureFormat::Srgb8Alpha8,
+            _ => self,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap {
+    Repeat,
+    ClampToEdge,
+    MirroredRepeat,
+}
+
+impl Wrap {
+    pub fn to_gl(self) -> gl::types::GLenum {
+        match self {
+            Wrap::Repeat => gl::REPEAT,
+            Wrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+            Wrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+        }
+    }
+}
+
+/// size, format and sampler parameters of a 2D texture
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureDesc {
+    pub width: usize,
+    pub height: usize,
+    pub format: TextureFormat,
+    pub min_filter: Filter,
+    pub mag_filter: Filter,
+    pub wrap_s: Wrap,
+    pub wrap_t: Wrap,
+    /// generate the mipmaps when the image is set, and sample them in minification
+    pub mipmap: bool,
+    /// maximum anisotropy. 1.0 disables the anisotropic filtering.
+    /// This is ignored without `GL_EXT_texture_filter_anisotropic`
+    pub anisotropy: f32,
+}
+
+impl TextureDesc {
+    /// linear filter, clamp to edge and no mipmap
+    pub fn new(width: usize, height: usize, format: TextureFormat) -> Self {
+        TextureDesc {
+            width,
+            height,
+            format,
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Linear,
+            wrap_s: Wrap::ClampToEdge,
+            wrap_t: Wrap::ClampToEdge,
+            mipmap: false,
+            anisotropy: 1.0,
+        }
+    }
+
+    pub fn filter(mut self, min_filter: Filter, mag_filter: Filter) -> Self {
+        self.min_filter = min_filter;
+        self.mag_filter = mag_filter;
+        self
+    }
+
+    pub fn wrap(mut self, wrap_s: Wrap, wrap_t: Wrap) -> Self {
+        self.wrap_s = wrap_s;
+        self.wrap_t = wrap_t;
+        self
+    }
+
+    pub fn mipmap(mut self, mipmap: bool) -> Self {
+        self.mipmap = mipmap;
+        self
+    }
+
+    pub fn anisotropy(mut self, anisotropy: f32) -> Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    /// store the color in sRGB (see `TextureFormat::srgb`)
+    pub fn srgb(mut self) -> Self {
+        self.format = self.format.srgb();
+        self
+    }
+
+    /// size of the client data of the whole image in bytes
+    pub fn num_bytes(&self) -> usize {
+        self.width * self.height * self.format.bytes_per_pixel()
+    }
+
+    fn min_filter_to_gl(&self) -> gl::types::GLenum {
+        match (self.min_filter, self.mipmap) {
+            (Filter::Nearest, false) => gl::NEAREST,
+            (Filter::Linear, false) => gl::LINEAR,
+            (Filter::Nearest, true) => gl::NEAREST_MIPMAP_NEAREST,
+            (Filter::Linear, true) => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+
+    fn mag_filter_to_gl(&self) -> gl::types::GLenum {
+        match self.mag_filter {
+            Filter::Nearest => gl::NEAREST,
+            Filter::Linear => gl::LINEAR,
+        }
+    }
+}
+
+/// call `f` with the pixel rows packed without padding (`pname` is `gl::UNPACK_ALIGNMENT`
+/// or `gl::PACK_ALIGNMENT`), and restore the previous alignment
+pub(crate) fn with_byte_alignment<R>(
+    gl: &gl::Gl,
+    pname: gl::types::GLenum,
+    f: impl FnOnce() -> R,
+) -> R {
+    let mut prev: gl::types::GLint = 4;
+    unsafe {
+        gl.GetIntegerv(pname, &mut prev);
+        gl.PixelStorei(pname, 1);
+    }
+    let res = f();
+    unsafe {
+        gl.PixelStorei(pname, prev);
+    }
+    res
+}
+
+pub struct Texture {
+    gl: Rc<gl::Gl>,
+    id: gl::types::GLuint,
+    target: gl::types::GLenum,
+    desc: Option<TextureDesc>,
+}
+
+impl Texture {
+    /// generate a texture for `target` (e.g., `gl::TEXTURE_2D`)
+    pub fn new(gl: &Rc<gl::Gl>, target: gl::types::GLenum) -> Self {
+        let mut id = 0;
+        unsafe {
+            gl.GenTextures(1, &mut id);
+        }
+        Texture {
+            gl: Rc::clone(gl),
+            id,
+            target,
+            desc: None,
+        }
+    }
+
+    /// 2D texture allocated as `desc`. The content is undefined if `data` is `None`
+    pub fn from_desc<T: Copy>(gl: &Rc<gl::Gl>, desc: &TextureDesc, data: Option<&[T]>) -> Self {
+        let mut tex = Texture::new(gl, gl::TEXTURE_2D);
+        tex.set_image(desc, data);
+        tex
+    }
+
+    pub fn id(&self) -> gl::types::GLuint {
+        self.id
+    }
+
+    pub fn target(&self) -> gl::types::GLenum {
+        self.target
+    }
+
+    /// name shown in the driver messages (see `crate::debug`)
+    pub fn set_label(&self, label: &str) {
+        crate::debug::object_label(&self.gl, gl::TEXTURE, self.id, label);
+    }
+
+    /// description of the last image set by `set_image`
+    pub fn desc(&self) -> Option<&TextureDesc> {
+        self.desc.as_ref()
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            self.gl.BindTexture(self.target, self.id);
+        }
+    }
+
+    /// (re)allocate the storage as `desc` with `data` and set the sampler parameters.
+    /// The texture is left bound
+    pub fn set_image<T: Copy>(&mut self, desc: &TextureDesc, data: Option<&[T]>) {
+        let gl = &self.gl;
+        let (internal, format, type_) = desc.format.to_gl();
+        let ptr = match data {
+            Some(data) => {
+                assert_eq!(std::mem::size_of_val(data), desc.num_bytes());
+                data.as_ptr() as *const _
+            }
+            None => std::ptr::null(),
+        };
+        self.bind();
+        unsafe {
+            with_byte_alignment(gl, gl::UNPACK_ALIGNMENT, || {
+                gl.TexImage2D(
+                    self.target,
+                    0,
+                    internal,
+                    desc.width as gl::types::GLsizei,
+                    desc.height as gl::types::GLsizei,
+                    0,
+                    format,
+                    type_,
+                    ptr,
+                )
+            });
+            let target = self.target;
+            let set_param = |pname, param: gl::types::GLenum| {
+                gl.TexParameteri(target, pname, param as gl::types::GLint)
+            };
+            set_param(gl::TEXTURE_MIN_FILTER, desc.min_filter_to_gl());
+            set_param(gl::TEXTURE_MAG_FILTER, desc.mag_filter_to_gl());
+            set_param(gl::TEXTURE_WRAP_S, desc.wrap_s.to_gl());
+            set_param(gl::TEXTURE_WRAP_T, desc.wrap_t.to_gl());
+            if desc.anisotropy > 1.0
+                && crate::has_extension(gl, "GL_EXT_texture_filter_anisotropic")
+            {
+                let mut max_anisotropy = 1.0;
+                gl.GetFloatv(gl::MAX_TEXTURE_MAX_ANISOTROPY_EXT, &mut max_anisotropy);
+                gl.TexParameterf(
+                    target,
+                    gl::TEXTURE_MAX_ANISOTROPY_EXT,
+                    desc.anisotropy.min(max_anisotropy),
+                );
+            }
+            if desc.mipmap && data.is_some() {
+                gl.GenerateMipmap(target);
+            }
+        }
+        crate::debug::check_error(&self.gl, "uploading texture image");
+        self.desc = Some(*desc);
+    }
+
+    /// overwrite the `width`x`height` region at (`x`,`y`) of the level 0 with `data`
+    /// in the format given to `set_image`. The mipmaps are regenerated if enabled
+    pub fn set_sub_image<T: Copy>(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        data: &[T],
+    ) {
+        let desc = self
+            .desc
+            .expect("the storage needs to be allocated by `set_image`");
+        assert!(x + width <= desc.width && y + height <= desc.height);
+        assert_eq!(
+            std::mem::size_of_val(data),
+            width * height * desc.format.bytes_per_pixel()
+        );
+        let (_, format, type_) = desc.format.to_gl();
+        self.bind();
+        unsafe {
+            let gl = &self.gl;
+            with_byte_alignment(gl, gl::UNPACK_ALIGNMENT, || {
+                gl.TexSubImage2D(
+                    self.target,
+                    0,
+                    x as gl::types::GLint,
+                    y as gl::types::GLint,
+                    width as gl::types::GLsizei,
+                    height as gl::types::GLsizei,
+                    format,
+                    type_,
+                    data.as_ptr() as *const _,
+                )
+            });
+            if desc.mipmap {
+                gl.GenerateMipmap(self.target);
+            }
+        }
+        crate::debug::check_error(&self.gl, "uploading texture sub-image");
+    }
+
+    /// release the ownership of the texture object without deleting it
+    pub fn into_raw(self) -> gl::types::GLuint {
+        let id = self.id;
+        std::mem::forget(self);
+        id
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+/// the linear filtering of the 32-bit float textures. Always available in the desktop OpenGL
+pub fn is_float_linear_supported(gl: &gl::Gl) -> bool {
+    !crate::is_gles(gl) || crate::has_extension(gl, "GL_OES_texture_float_linear")
+}
+
+#[cfg(feature = "image")]
+impl Texture {
+    /// load a PNG, JPEG, EXR, etc. file as a 2D texture (see `from_dynamic_image`)
+    pub fn from_path<P: AsRef<std::path::Path>>(
+        gl: &Rc<gl::Gl>,
+        path: P,
+        srgb: bool,
+    ) -> Result<Self, image::ImageError> {
+        let img = image::ImageReader::open(path)?
+            .with_guessed_format()?
+            .decode()?;
+        Ok(Self::from_dynamic_image(gl, &img, srgb))
+    }
+
+    /// 2D texture of `img`. The rows are flipped so that the first row of the image is at v=1.
+    /// The 8-bit color images are stored in sRGB if `srgb` is true, and have mipmaps.
+    /// The gray images are read as (L,L,L) or (L,L,L,A) in the shader.
+    ///
+    /// The 16-bit and float images are converted to 32-bit float without mipmaps:
+    /// the 16-bit gray image is normalized to [0, 1] as `R32F`, the float RGB as `Rgb32F`
+    /// and the others (including the 16-bit color images) as `Rgba32F`.
+    /// The 32-bit float textures are not filterable in OpenGL ES without
+    /// `GL_OES_texture_float_linear`, so they are sampled with the nearest filter there
+    pub fn from_dynamic_image(gl: &Rc<gl::Gl>, img: &image::DynamicImage, srgb: bool) -> Self {
+        use image::DynamicImage;
+        let img = img.flipv();
+        let (w, h) = (img.width() as usize, img.height() as usize);
+        let (desc, swizzle) = match &img {
+            DynamicImage::ImageLuma8(_) => (TextureDesc::new(w, h, TextureFormat::R8), true),
+            DynamicImage::ImageLumaA8(_) => (TextureDesc::new(w, h, TextureFormat::Rg8), true),
+            DynamicImage::ImageRgb8(_) => (TextureDesc::new(w, h, TextureFormat::Rgb8), false),
+            DynamicImage::ImageRgba8(_) => (TextureDesc::new(w, h, TextureFormat::Rgba8), false),
+            DynamicImage::ImageLuma16(_) => (TextureDesc::new(w, h, TextureFormat::R32F), true),
+            DynamicImage::ImageRgb32F(_) => (TextureDesc::new(w, h, TextureFormat::Rgb32F), false),
+            _ => (TextureDesc::new(w, h, TextureFormat::Rgba32F), false),
+        };
+        let is_float = matches!(
+            desc.format,
+            TextureFormat::R32F | TextureFormat::Rgb32F | TextureFormat::Rgba32F
+        );
+        let desc = if is_float && !is_float_linear_supported(gl) {
+            desc.filter(Filter::Nearest, Filter::Nearest)
+        } else {
+            desc
+        };
+        let mut tex = Texture::new(gl, gl::TEXTURE_2D);
+        match desc.format {
+            TextureFormat::R8 | TextureFormat::Rg8 | TextureFormat::Rgb8 | TextureFormat::Rgba8 => {
+                let desc = if srgb { desc.srgb() } else { desc };
+                tex.set_image(&desc.mipmap(true), Some(img.as_bytes()));
+            }
+            TextureFormat::R32F => {
+                let data: Vec<f32> = img
+                    .to_luma16()
+                    .iter()
+                    .map(|&v| v as f32 / u16::MAX as f32)
+                    .collect();
+                tex.set_image(&desc, Some(&data));
+            }
+            TextureFormat::Rgb32F => tex.set_image(&desc, Some(img.to_rgb32f().as_raw())),
+            _ => tex.set_image(&desc, Some(img.to_rgba32f().as_raw())),
+        }
+        if swizzle {
+            // the second channel of the two-channel image is the alpha
+            let alpha = if desc.format == TextureFormat::Rg8 {
+                gl::GREEN
+            } else {
+                gl::ONE
+            };
+            unsafe {
+                gl.TexParameteri(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_SWIZZLE_G,
+                    gl::RED as gl::types::GLint,
+                );
+                gl.TexParameteri(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_SWIZZLE_B,
+                    gl::RED as gl::types::GLint,
+                );
+                gl.TexParameteri(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_SWIZZLE_A,
+                    alpha as gl::types::GLint,
+                );
+            }
+        }
+        tex
+    }
+}
@@ -1,48 +1,51 @@
 use crate::gl;
+use std::rc::Rc;
 
+/// compile the vertex and fragment shaders and link them into a program
+///
+/// # Safety
+/// The GL context of `gl` needs to be current.
 pub unsafe fn compile_shaders(
     gl: &gl::Gl,
     src_vertex: &[u8],
     src_fragment: &[u8],
-) -> gl::types::GLuint {
-    let vs = gl.CreateShader(gl::VERTEX_SHADER);
-    gl.ShaderSource(
-        vs,
-        1,
-        [src_vertex.as_ptr() as *const _].as_ptr(),
-        std::ptr::null(),
-    );
-    gl.CompileShader(vs);
-
-    let fs = gl.CreateShader(gl::FRAGMENT_SHADER);
-    gl.ShaderSource(
-        fs,
-        1,
-        [src_fragment.as_ptr() as *const _].as_ptr(),
-        std::ptr::null(),
-    );
-    gl.CompileShader(fs);
+) -> Result<gl::types::GLuint, crate::ShaderError> {
+    let vs = crate::create_shader(gl, gl::VERTEX_SHADER, src_vertex)?;
+    let fs = match crate::create_shader(gl, gl::FRAGMENT_SHADER, src_fragment) {
+        Ok(fs) => fs,
+        Err(e) => {
+            gl.DeleteShader(vs);
+            return Err(e);
+        }
+    };
+    crate::link_program(gl, &[vs, fs])
+}
 
-    let id_program = gl.CreateProgram();
-    gl.AttachShader(id_program, vs);
-    gl.AttachShader(id_program, fs);
-    gl.LinkProgram(id_program);
-    assert!(gl.IsProgram(id_program) != 0);
-    {
-        let mut success: gl::types::GLint = 0;
-        gl.GetProgramiv(id_program, gl::LINK_STATUS, &mut success);
-        if success == 0 {
-            let info_log: [i8; 512] = [0; 512];
-            let mut length: i32 = 512;
-            gl.GetProgramInfoLog(id_program, 512, &mut length, info_log.as_ptr() as *mut _);
-            println!("{}", length);
-            let info_log0 = String::from_utf8(info_log.iter().map(|&c| c as u8).collect());
-            println!("ERROR::SHADER::PROGRAM::LINKING_FAILED {:?}", info_log0);
+/// compile the vertex, geometry and fragment shaders and link them into a program
+///
+/// # Safety
+/// The GL context of `gl` needs to be current.
+pub unsafe fn compile_shaders_with_geometry(
+    gl: &gl::Gl,
+    src_vertex: &[u8],
+    src_geometry: &[u8],
+    src_fragment: &[u8],
+) -> Result<gl::types::GLuint, crate::ShaderError> {
+    let mut shaders = vec![];
+    for (shader_type, src) in [
+        (gl::VERTEX_SHADER, src_vertex),
+        (gl::GEOMETRY_SHADER, src_geometry),
+        (gl::FRAGMENT_SHADER, src_fragment),
+    ] {
+        match crate::create_shader(gl, shader_type, src) {
+            Ok(shader) => shaders.push(shader),
+            Err(e) => {
+                shaders.iter().for_each(|&s| gl.DeleteShader(s));
+                return Err(e);
+            }
         }
     }
-    gl.DeleteShader(vs);
-    gl.DeleteShader(fs);
-    id_program
+    crate::link_program(gl, &shaders)
 }
 
 pub unsafe fn get_uniform_location(
@@ -63,30 +66,29 @@ pub unsafe fn get_attrib_location(
     gl.GetAttribLocation(id_program, cname.as_ptr())
 }
 
+/// 2D texture of `width`x`height` texels of `format`, which has mipmaps if the format is
+/// 8 bits per channel. The caller owns the returned texture object
+///
+/// # Safety
+/// The GL context of `gl` needs to be current.
 pub unsafe fn gen_texture(
-    gl: &gl::Gl,
+    gl: &Rc<gl::Gl>,
     width: gl::types::GLsizei,
     height: gl::types::GLsizei,
     data: &[u8],
-    format: gl::types::GLenum,
+    format: crate::texture::TextureFormat,
 ) -> gl::types::GLuint {
-    gl.Enable(gl::TEXTURE_2D);
-    gl.ActiveTexture(gl::TEXTURE0);
-    let mut id_tex: gl::types::GLuint = 0;
-    gl.GenTextures(1, &mut id_tex);
-    gl.BindTexture(gl::TEXTURE_2D, id_tex);
-    gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
-    gl.TexImage2D(
-        gl::TEXTURE_2D,
-        0,
-        gl::RGB.try_into().unwrap(),
-        width,
-        height,
-        0,
+    use crate::texture::{Texture, TextureDesc, TextureFormat};
+    let mipmap = matches!(
         format,
-        gl::UNSIGNED_BYTE,
-        data.as_ptr() as *const _,
+        TextureFormat::R8
+            | TextureFormat::Rg8
+            | TextureFormat::Rgb8
+            | TextureFormat::Rgba8
+            | TextureFormat::Srgb8
+            | TextureFormat::Srgb8Alpha8
     );
-    gl.GenerateMipmap(gl::TEXTURE_2D);
-    id_tex
+    let desc = TextureDesc::new(width as usize, height as usize, format).mipmap(mipmap);
+    gl.ActiveTexture(gl::TEXTURE0);
+    Texture::from_desc(gl, &desc, Some(data)).into_raw()
 }
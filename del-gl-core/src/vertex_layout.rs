@@ -0,0 +1,176 @@
+//! description of the vertex attributes in a vertex buffer,
+//! which is bound to the attributes of a program by name
+
+use crate::gl;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VertexAttribute {
+    /// name of the attribute in the vertex shader
+    pub name: String,
+    /// number of components (1 to 4)
+    pub num_component: gl::types::GLint,
+    /// type of a component such as `gl::FLOAT`
+    pub type_: gl::types::GLenum,
+    /// map the integer types to [0,1] or [-1,1]. The integer types not normalized are passed
+    /// as they are, so the shader input needs to be `int`, `ivec*`, `uint` or `uvec*`
+    pub normalized: bool,
+    /// offset in bytes from the beginning of the vertex
+    pub offset: usize,
+    /// number of instances sharing the value. zero for per-vertex attribute
+    pub divisor: gl::types::GLuint,
+}
+
+impl VertexAttribute {
+    pub fn new(
+        name: &str,
+        num_component: gl::types::GLint,
+        type_: gl::types::GLenum,
+        offset: usize,
+    ) -> Self {
+        VertexAttribute {
+            name: name.to_string(),
+            num_component,
+            type_,
+            normalized: false,
+            offset,
+            divisor: 0,
+        }
+    }
+
+    pub fn normalized(mut self, normalized: bool) -> Self {
+        self.normalized = normalized;
+        self
+    }
+
+    pub fn divisor(mut self, divisor: gl::types::GLuint) -> Self {
+        self.divisor = divisor;
+        self
+    }
+
+    /// integer type not normalized, which is read by the shader without conversion to float
+    pub fn is_integer(&self) -> bool {
+        !self.normalized
+            && matches!(
+                self.type_,
+                gl::BYTE
+                    | gl::UNSIGNED_BYTE
+                    | gl::SHORT
+                    | gl::UNSIGNED_SHORT
+                    | gl::INT
+                    | gl::UNSIGNED_INT
+            )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VertexLayout {
+    /// size of a vertex in bytes
+    pub stride: usize,
+    pub attributes: Vec<VertexAttribute>,
+}
+
+impl VertexLayout {
+    pub fn new(stride: usize) -> Self {
+        VertexLayout {
+            stride,
+            attributes: vec![],
+        }
+    }
+
+    /// layout of the vertex type `V`
+    pub fn of<V: Vertex>() -> Self {
+        V::layout()
+    }
+
+    pub fn with_attribute(mut self, attribute: VertexAttribute) -> Self {
+        self.attributes.push(attribute);
+        self
+    }
+
+    /// add the attribute of `num_component` floats at `offset` bytes
+    pub fn with_float(self, name: &str, num_component: gl::types::GLint, offset: usize) -> Self {
+        self.with_attribute(VertexAttribute::new(name, num_component, gl::FLOAT, offset))
+    }
+
+    /// set the attribute pointers of the bound vertex buffer to the attributes of `program`.
+    /// The attributes not active in the program are skipped
+    pub fn bind(&self, gl: &gl::Gl, program: &crate::shader_program::ShaderProgram) {
+        for attribute in &self.attributes {
+            let location = program.attrib_location(&attribute.name);
+            if location < 0 {
+                continue;
+            }
+            let location = location as gl::types::GLuint;
+            unsafe {
+                gl.EnableVertexAttribArray(location);
+                if attribute.is_integer() {
+                    gl.VertexAttribIPointer(
+                        location,
+                        attribute.num_component,
+                        attribute.type_,
+                        self.stride as gl::types::GLsizei,
+                        attribute.offset as *const _,
+                    );
+                } else {
+                    gl.VertexAttribPointer(
+                        location,
+                        attribute.num_component,
+                        attribute.type_,
+                        attribute.normalized as gl::types::GLboolean,
+                        self.stride as gl::types::GLsizei,
+                        attribute.offset as *const _,
+                    );
+                }
+                // reset to zero as well, since the divisor is kept in the vertex array
+                // after the location is rebound. Not available in OpenGL ES 2.0
+                if attribute.divisor != 0 || gl.VertexAttribDivisor.is_loaded() {
+                    gl.VertexAttribDivisor(location, attribute.divisor);
+                }
+            }
+        }
+    }
+}
+
+/// `#[repr(C)]` struct uploaded as an interleaved vertex buffer.
+/// This can be derived with `#[derive(Vertex)]` (the `derive` feature)
+pub trait Vertex: Copy {
+    fn layout() -> VertexLayout;
+}
+
+/// type of a field of a vertex struct
+pub trait VertexAttributeValue {
+    const NUM_COMPONENT: gl::types::GLint;
+    const TYPE: gl::types::GLenum;
+}
+
+macro_rules! impl_vertex_attribute_value {
+    ($t: ty, $type_: expr) => {
+        impl VertexAttributeValue for $t {
+            const NUM_COMPONENT: gl::types::GLint = 1;
+            const TYPE: gl::types::GLenum = $type_;
+        }
+        impl VertexAttributeValue for [$t; 2] {
+            const NUM_COMPONENT: gl::types::GLint = 2;
+            const TYPE: gl::types::GLenum = $type_;
+        }
+        impl VertexAttributeValue for [$t; 3] {
+            const NUM_COMPONENT: gl::types::GLint = 3;
+            const TYPE: gl::types::GLenum = $type_;
+        }
+        impl VertexAttributeValue for [$t; 4] {
+            const NUM_COMPONENT: gl::types::GLint = 4;
+            const TYPE: gl::types::GLenum = $type_;
+        }
+    };
+}
+
+impl_vertex_attribute_value!(f32, gl::FLOAT);
+impl_vertex_attribute_value!(i8, gl::BYTE);
+impl_vertex_attribute_value!(u8, gl::UNSIGNED_BYTE);
+impl_vertex_attribute_value!(i16, gl::SHORT);
+impl_vertex_attribute_value!(u16, gl::UNSIGNED_SHORT);
+impl_vertex_attribute_value!(i32, gl::INT);
+impl_vertex_attribute_value!(u32, gl::UNSIGNED_INT);
+
+#[cfg(feature = "derive")]
+pub use del_gl_derive::Vertex;
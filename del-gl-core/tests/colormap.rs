@@ -0,0 +1,49 @@
+//! tests of the color tables of `colormap`
+
+use del_gl_core::colormap::{self, ColormapError, Preset};
+
+#[test]
+fn presets_are_found_by_name_and_reversed() {
+    for preset in Preset::ALL {
+        let colors = preset.colors();
+        assert_eq!(colors.len(), colormap::NUM_STOP);
+        assert_eq!(colormap::from_name(preset.name()).unwrap(), colors);
+        let name_r = format!("{}_r", preset.name());
+        let colors_r = colormap::from_name(&name_r).unwrap();
+        assert_eq!(colors_r.first(), colors.last());
+        assert_eq!(colors_r.last(), colors.first());
+    }
+    assert!(matches!(
+        colormap::from_name("rainbow"),
+        Err(ColormapError::UnknownName(_))
+    ));
+}
+
+#[test]
+fn stops_are_parsed_from_csv() {
+    let text = "# blue to red\nr,g,b\n0, 0, 1\n\n1.0,0.0,0.0\n";
+    assert_eq!(
+        colormap::from_csv(text).unwrap(),
+        [[0.0, 0.0, 1.0], [1.0, 0.0, 0.0]]
+    );
+    assert!(matches!(
+        colormap::from_csv("0,0,1\n0,2,0\n"),
+        Err(ColormapError::Parse { line: 2, .. })
+    ));
+    assert!(matches!(
+        colormap::from_csv("0,0,1\n"),
+        Err(ColormapError::TooFewColors(1))
+    ));
+}
+
+#[test]
+fn resampled_colors_are_on_the_same_map() {
+    let colors = [[0.0, 0.0, 1.0], [1.0, 1.0, 1.0], [1.0, 0.0, 0.0]];
+    let resampled = colormap::resample(&colors, 5);
+    assert_eq!(resampled.len(), 5);
+    assert_eq!(resampled[0], colors[0]);
+    assert_eq!(resampled[1], [0.5, 0.5, 1.0]);
+    assert_eq!(resampled[2], colors[1]);
+    assert_eq!(resampled[4], colors[2]);
+    assert_eq!(colormap::resample(&resampled, 3), colors);
+}
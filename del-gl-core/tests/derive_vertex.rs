@@ -0,0 +1,34 @@
+//! tests of `#[derive(Vertex)]` (the `derive` feature)
+#![cfg(feature = "derive")]
+
+use del_gl_core::gl;
+use del_gl_core::vertex_layout::{Vertex, VertexAttribute, VertexLayout};
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, Vertex)]
+#[repr(C)]
+struct Particle {
+    position: [f32; 3],
+    #[vertex(name = "color", normalized)]
+    rgba: [u8; 4],
+    #[vertex(divisor = 1)]
+    offset: [f32; 2],
+    label: i32,
+}
+
+#[test]
+fn layout_is_derived_from_fields() {
+    let layout = VertexLayout::of::<Particle>();
+    assert_eq!(layout.stride, std::mem::size_of::<Particle>());
+    assert_eq!(layout.stride, 28);
+    assert_eq!(
+        layout.attributes,
+        [
+            VertexAttribute::new("position", 3, gl::FLOAT, 0),
+            VertexAttribute::new("color", 4, gl::UNSIGNED_BYTE, 12).normalized(true),
+            VertexAttribute::new("offset", 2, gl::FLOAT, 16).divisor(1),
+            VertexAttribute::new("label", 1, gl::INT, 24),
+        ]
+    );
+    assert_eq!(Particle::layout(), layout);
+}
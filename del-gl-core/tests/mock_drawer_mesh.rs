@@ -0,0 +1,581 @@
+//! tests of the mesh drawers against the recording fake of GL (`mock_gl`)
+
+use del_gl_core::drawer_mesh::{ColorSource, Drawer, ElementId};
+use del_gl_core::gl;
+use del_gl_core::mock_gl::{self, ObjectKind};
+use del_gl_core::render_state::{Blend, RenderState};
+use del_gl_core::shader_program::{ShaderProgram, ShaderSource};
+use del_gl_core::vertex_layout::{VertexAttribute, VertexLayout};
+use std::rc::Rc;
+
+#[rustfmt::skip]
+const IDENTITY: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 1.0, 0.0,
+    0.0, 0.0, 0.0, 1.0,
+];
+
+/// a quad made of two triangles with its four boundary edges
+fn quad_drawer(gl: &Rc<gl::Gl>) -> (Drawer, [ElementId; 2]) {
+    let vtx2xyz = vec![0., 0., 0., 1., 0., 0., 1., 1., 0., 0., 1., 0.];
+    let tri2vtx: Vec<usize> = vec![0, 1, 2, 0, 2, 3];
+    let edge2vtx: Vec<u32> = vec![0, 1, 1, 2, 2, 3, 3, 0];
+    let mut drawer = Drawer::new();
+    drawer.compile_shader(gl).unwrap();
+    drawer.update_vertex(gl, &vtx2xyz, 3);
+    let id_tri = drawer.add_element(gl, gl::TRIANGLES, &tri2vtx, [1.0, 0.0, 0.0]);
+    let id_edge = drawer.add_element(gl, gl::LINES, &edge2vtx, [0.0, 0.0, 0.0]);
+    // as the viewers do before drawing
+    unsafe { gl.Enable(gl::DEPTH_TEST) };
+    (drawer, [id_tri, id_edge])
+}
+
+#[test]
+fn draw_issues_one_draw_elements_per_ebo() {
+    let gl = mock_gl::load();
+    let (drawer, _) = quad_drawer(&gl);
+    mock_gl::take_calls();
+    drawer.draw(&gl, &IDENTITY, &IDENTITY);
+    let draws: Vec<Vec<i64>> = mock_gl::calls_named("DrawElements")
+        .into_iter()
+        .map(|c| c.args)
+        .collect();
+    assert_eq!(
+        draws,
+        vec![
+            vec![gl::TRIANGLES as i64, 6, gl::UNSIGNED_SHORT as i64, 0],
+            vec![gl::LINES as i64, 8, gl::UNSIGNED_INT as i64, 0],
+        ]
+    );
+    // the color is set before each draw call
+    let program = mock_gl::calls_named("UseProgram")[0].args[0] as gl::types::GLuint;
+    let loc_color = unsafe { gl.GetUniformLocation(program, c"color".as_ptr()) } as i64;
+    let names: Vec<&str> = mock_gl::calls()
+        .iter()
+        .filter(|c| c.name == "DrawElements" || (c.name == "Uniform3fv" && c.args[0] == loc_color))
+        .map(|c| c.name)
+        .collect();
+    assert_eq!(
+        names,
+        ["Uniform3fv", "DrawElements", "Uniform3fv", "DrawElements"]
+    );
+}
+
+#[test]
+fn lighting_is_enabled_only_for_triangles_with_normals() {
+    let gl = mock_gl::load();
+    let (mut drawer, _) = quad_drawer(&gl);
+    let lighting_values = |drawer: &Drawer| {
+        mock_gl::take_calls();
+        drawer.draw(&gl, &IDENTITY, &IDENTITY);
+        let program = mock_gl::calls_named("UseProgram")[0].args[0] as gl::types::GLuint;
+        let loc = unsafe { gl.GetUniformLocation(program, c"lighting".as_ptr()) } as i64;
+        mock_gl::calls_named("Uniform1i")
+            .into_iter()
+            .filter(|c| c.args[0] == loc)
+            .map(|c| c.args[1])
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(lighting_values(&drawer), [0, 0]);
+    drawer.update_normal_from_triangles(&gl, &[0usize, 1, 2, 0, 2, 3], &[0.; 12]);
+    assert_eq!(lighting_values(&drawer), [1, 0]);
+}
+
+#[test]
+fn flat_shading_enables_lighting_without_normals() {
+    let gl = mock_gl::load();
+    let (mut drawer, _) = quad_drawer(&gl);
+    drawer.flat_shading = true;
+    mock_gl::take_calls();
+    drawer.draw(&gl, &IDENTITY, &IDENTITY);
+    let program = mock_gl::calls_named("UseProgram")[0].args[0] as gl::types::GLuint;
+    let loc = unsafe { gl.GetUniformLocation(program, c"lighting".as_ptr()) } as i64;
+    let lighting: Vec<i64> = mock_gl::calls_named("Uniform1i")
+        .into_iter()
+        .filter(|c| c.args[0] == loc)
+        .map(|c| c.args[1])
+        .collect();
+    assert_eq!(lighting, [1, 0]);
+    // the vertices are not duplicated
+    assert_eq!(mock_gl::num_live(ObjectKind::Buffer), 3);
+}
+
+#[test]
+fn wireframe_program_is_used_only_for_triangles() {
+    let gl = mock_gl::load();
+    let (mut drawer, _) = quad_drawer(&gl);
+    let wireframe = del_gl_core::drawer_mesh::Wireframe::new([0.0, 0.0, 1.0]);
+    drawer.set_wireframe(&gl, Some(wireframe)).unwrap();
+    assert_eq!(mock_gl::num_live(ObjectKind::Program), 2);
+    mock_gl::take_calls();
+    drawer.draw(&gl, &IDENTITY, &IDENTITY);
+    let programs: Vec<i64> = mock_gl::calls()
+        .iter()
+        .filter(|c| c.name == "UseProgram")
+        .map(|c| c.args[0])
+        .collect();
+    // the wireframe program for the triangles and the original one for the lines
+    assert_eq!(programs.len(), 2);
+    assert_ne!(programs[0], programs[1]);
+    assert_eq!(mock_gl::calls_named("DrawElements").len(), 2);
+    // back to the original program without the wireframe
+    drawer.set_wireframe(&gl, None).unwrap();
+    mock_gl::take_calls();
+    drawer.draw(&gl, &IDENTITY, &IDENTITY);
+    assert_eq!(mock_gl::calls_named("UseProgram").len(), 1);
+    assert_eq!(mock_gl::calls_named("UseProgram")[0].args[0], programs[1]);
+}
+
+#[test]
+fn wireframe_program_is_compiled_from_files_and_reloaded() {
+    let gl = mock_gl::load();
+    let (mut drawer, _) = quad_drawer(&gl);
+    let dir = std::env::temp_dir().join(format!("del_gl_wire_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (path_vs, path_gs, path_fs) = (
+        dir.join("wire.vs"),
+        dir.join("wire.gs"),
+        dir.join("wire.fs"),
+    );
+    std::fs::write(&path_gs, "void main() {}\n").unwrap();
+    std::fs::write(&path_fs, "void main() {}\n").unwrap();
+    // the attributes are not at the locations of the main program
+    std::fs::write(
+        &path_vs,
+        "in vec4 vtx_color;\nin vec3 position;\nvoid main() {}\n",
+    )
+    .unwrap();
+    let err = drawer
+        .compile_wireframe_shader_from_files(&gl, &path_vs, &path_gs, &path_fs)
+        .unwrap_err();
+    assert_eq!(err.stage, del_gl_core::ShaderStage::Link);
+    std::fs::write(&path_vs, "#include \"mesh_vertex\"\n").unwrap();
+    drawer
+        .compile_wireframe_shader_from_files(&gl, &path_vs, &path_gs, &path_fs)
+        .unwrap();
+    let wireframe = del_gl_core::drawer_mesh::Wireframe::new([0.0, 0.0, 1.0]);
+    drawer.set_wireframe(&gl, Some(wireframe)).unwrap();
+    // modified later than the compilation
+    let file = std::fs::File::options().write(true).open(&path_gs).unwrap();
+    file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(10))
+        .unwrap();
+    mock_gl::take_calls();
+    assert!(drawer.reload_shader_if_changed(&gl).unwrap());
+    assert_eq!(mock_gl::calls_named("LinkProgram").len(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn render_state_is_changed_only_around_its_elements() {
+    let gl = mock_gl::load();
+    let (mut drawer, [id_tri, _]) = quad_drawer(&gl);
+    *drawer.render_state_mut(id_tri).unwrap() = RenderState::default()
+        .polygon_offset(1.0, 2.0)
+        .depth_write(false);
+    mock_gl::take_calls();
+    drawer.draw(&gl, &IDENTITY, &IDENTITY);
+    let names: Vec<&str> = mock_gl::calls()
+        .iter()
+        .map(|c| c.name)
+        .filter(|&n| {
+            [
+                "Enable",
+                "Disable",
+                "PolygonOffset",
+                "DepthMask",
+                "DrawElements",
+            ]
+            .contains(&n)
+        })
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "Enable",
+            "PolygonOffset",
+            "DepthMask",
+            "DrawElements",
+            "Disable",
+            "DepthMask",
+            "DrawElements",
+        ]
+    );
+    assert_eq!(
+        mock_gl::calls_named("Enable")[0].args,
+        [gl::POLYGON_OFFSET_FILL as i64]
+    );
+    assert_eq!(mock_gl::calls_named("DepthMask")[1].args, [gl::TRUE as i64]);
+    // nothing is changed for the default state
+    *drawer.render_state_mut(id_tri).unwrap() = RenderState::default();
+    mock_gl::take_calls();
+    drawer.draw(&gl, &IDENTITY, &IDENTITY);
+    assert!(mock_gl::calls_named("Enable").is_empty());
+    assert!(mock_gl::calls_named("Disable").is_empty());
+}
+
+#[test]
+fn elements_are_hidden_replaced_and_removed_by_id() {
+    let gl = mock_gl::load();
+    let (mut drawer, [id_tri, id_edge]) = quad_drawer(&gl);
+    let draw_counts = |drawer: &Drawer| {
+        mock_gl::take_calls();
+        drawer.draw(&gl, &IDENTITY, &IDENTITY);
+        mock_gl::calls_named("DrawElements")
+            .into_iter()
+            .map(|c| c.args[1])
+            .collect::<Vec<_>>()
+    };
+    drawer.set_visible(id_edge, false);
+    assert_eq!(draw_counts(&drawer), [6]);
+    drawer.set_visible(id_edge, true);
+    // a single triangle
+    drawer.update_indices(id_tri, &[0u32, 1, 2]);
+    assert_eq!(draw_counts(&drawer), [3, 8]);
+    drawer.set_color(id_tri, [0.0, 1.0, 0.0]);
+    assert_eq!(
+        drawer.material_mut(id_tri).unwrap().diffuse,
+        [0.0, 1.0, 0.0]
+    );
+    assert!(drawer.remove_element(id_tri));
+    assert!(!drawer.remove_element(id_tri));
+    assert_eq!(draw_counts(&drawer), [8]);
+    // the element buffer of the triangles is deleted
+    assert_eq!(mock_gl::num_live(ObjectKind::Buffer), 2);
+    // the stale id is rejected without panicking
+    assert!(!drawer.set_color(id_tri, [1.0, 0.0, 0.0]));
+    assert!(!drawer.set_visible(id_tri, false));
+    assert!(!drawer.update_indices(id_tri, &[0u32, 1, 2]));
+    assert!(drawer.material_mut(id_tri).is_none());
+    assert!(drawer.render_state_mut(id_tri).is_none());
+    // the ids are not reused
+    let id_new = drawer.add_element(&gl, gl::POINTS, &vec![0u32], [0.0, 0.0, 1.0]);
+    assert_ne!(id_new, id_tri);
+}
+
+#[test]
+fn set_color_keeps_other_material_fields() {
+    let gl = mock_gl::load();
+    let (mut drawer, [id_tri, _]) = quad_drawer(&gl);
+    drawer.material_mut(id_tri).unwrap().alpha = 0.4;
+    drawer.material_mut(id_tri).unwrap().ambient = [0.1, 0.2, 0.3];
+    drawer.set_color(id_tri, [0.0, 0.0, 1.0]);
+    let material = *drawer.material_mut(id_tri).unwrap();
+    assert_eq!(material.diffuse, [0.0, 0.0, 1.0]);
+    assert_eq!(material.alpha, 0.4);
+    assert_eq!(material.ambient, [0.1, 0.2, 0.3]);
+}
+
+#[test]
+fn color_source_is_set_per_element() {
+    let gl = mock_gl::load();
+    let (mut drawer, [id_tri, id_edge]) = quad_drawer(&gl);
+    drawer.update_color_per_vertex(&gl, &[1.0; 12], 3);
+    drawer.set_color_source(id_edge, ColorSource::Vertex);
+    drawer.update_color_per_element(&gl, id_tri, &[1.0, 0.0, 0.0, 0.0, 1.0, 0.0], 3);
+    // one texel per triangle
+    let tex_images = mock_gl::calls_named("TexImage2D");
+    assert_eq!(tex_images.len(), 1);
+    assert_eq!(tex_images[0].args[3..5], [2, 1]);
+    mock_gl::take_calls();
+    drawer.draw(&gl, &IDENTITY, &IDENTITY);
+    let program = mock_gl::calls_named("UseProgram")[0].args[0] as gl::types::GLuint;
+    let loc = unsafe { gl.GetUniformLocation(program, c"color_source".as_ptr()) } as i64;
+    let sources: Vec<i64> = mock_gl::calls_named("Uniform1i")
+        .into_iter()
+        .filter(|c| c.args[0] == loc)
+        .map(|c| c.args[1])
+        .collect();
+    assert_eq!(
+        sources,
+        [ColorSource::Element as i64, ColorSource::Vertex as i64]
+    );
+    assert_eq!(mock_gl::calls_named("BindTexture").len(), 1);
+    // the texture is reused for the same number of elements
+    drawer.update_color_per_element(&gl, id_tri, &[0.0; 6], 3);
+    assert_eq!(mock_gl::calls_named("TexSubImage2D").len(), 1);
+    assert_eq!(mock_gl::num_live(ObjectKind::Texture), 1);
+}
+
+#[test]
+fn state_of_context_is_restored_after_draw() {
+    let gl = mock_gl::load();
+    let (mut drawer, [id_tri, id_edge]) = quad_drawer(&gl);
+    drawer.render_state_mut(id_edge).unwrap().depth_test = false;
+    unsafe {
+        gl.Disable(gl::DEPTH_TEST);
+        gl.Enable(gl::CULL_FACE);
+    }
+    mock_gl::take_calls();
+    drawer.draw(&gl, &IDENTITY, &IDENTITY);
+    let toggles: Vec<(&str, i64)> = mock_gl::calls()
+        .iter()
+        .filter(|c| ["Enable", "Disable"].contains(&c.name))
+        .map(|c| (c.name, c.args[0]))
+        .collect();
+    assert_eq!(
+        toggles,
+        [
+            ("Enable", gl::DEPTH_TEST as i64),
+            ("Disable", gl::CULL_FACE as i64),
+            ("Disable", gl::DEPTH_TEST as i64),
+            ("Enable", gl::CULL_FACE as i64),
+        ]
+    );
+    // the blend factors set by others are restored as they are
+    drawer.render_state_mut(id_tri).unwrap().blend = Some(Blend::Alpha);
+    unsafe { gl.Enable(gl::BLEND) };
+    mock_gl::take_calls();
+    drawer.draw(&gl, &IDENTITY, &IDENTITY);
+    assert_eq!(
+        mock_gl::calls_named("BlendFuncSeparate")[0].args,
+        [gl::ONE as i64, 0, gl::ONE as i64, 0]
+    );
+    assert_eq!(
+        mock_gl::calls_named("Enable").last().unwrap().args,
+        [gl::BLEND as i64]
+    );
+}
+
+#[test]
+fn weighted_oit_elements_are_drawn_only_in_transparent_pass() {
+    let gl = mock_gl::load();
+    let (mut drawer, [id_tri, id_edge]) = quad_drawer(&gl);
+    drawer.render_state_mut(id_tri).unwrap().blend = Some(Blend::WeightedOit);
+    *drawer.render_state_mut(id_edge).unwrap() = RenderState::default().blend(Some(Blend::Alpha));
+    mock_gl::take_calls();
+    drawer.draw(&gl, &IDENTITY, &IDENTITY);
+    let draws = mock_gl::calls_named("DrawElements");
+    assert_eq!(draws.len(), 1);
+    assert_eq!(draws[0].args[0], gl::LINES as i64);
+    assert_eq!(
+        mock_gl::calls_named("BlendFunc")[0].args,
+        [gl::SRC_ALPHA as i64, gl::ONE_MINUS_SRC_ALPHA as i64]
+    );
+    assert_eq!(
+        mock_gl::calls_named("Disable").last().unwrap().args,
+        [gl::BLEND as i64]
+    );
+    // the blend function of the transparent pass is left to `oit::Target`
+    mock_gl::take_calls();
+    drawer.draw_transparent(&gl, &IDENTITY, &IDENTITY);
+    let draws = mock_gl::calls_named("DrawElements");
+    assert_eq!(draws.len(), 1);
+    assert_eq!(draws[0].args[0], gl::TRIANGLES as i64);
+    assert!(mock_gl::calls_named("BlendFunc").is_empty());
+}
+
+#[test]
+fn oit_target_restores_state_and_checks_depth_format() {
+    use del_gl_core::oit::{OitError, Target};
+    use del_gl_core::texture::TextureFormat;
+    let gl = mock_gl::load();
+    unsafe { gl.Enable(gl::DEPTH_TEST) };
+    let mut target = Target::new(&gl, 4, 4, TextureFormat::Depth24).unwrap();
+    target.begin(&gl).unwrap();
+    mock_gl::take_calls();
+    target.end(&gl);
+    unsafe {
+        assert_eq!(gl.IsEnabled(gl::DEPTH_TEST), gl::TRUE);
+        assert_eq!(gl.IsEnabled(gl::BLEND), gl::FALSE);
+    }
+    assert_eq!(
+        mock_gl::calls_named("BlendFuncSeparate")[0].args,
+        [gl::ONE as i64, 0, gl::ONE as i64, 0]
+    );
+    assert_eq!(mock_gl::calls_named("DepthMask")[0].args, [gl::TRUE as i64]);
+    // the 24-bit depth of the window can not be copied into a float depth
+    let mut target = Target::new(&gl, 4, 4, TextureFormat::Depth32F).unwrap();
+    mock_gl::take_calls();
+    assert!(matches!(
+        target.begin(&gl),
+        Err(OitError::IncompatibleDepth { depth_size: 24, .. })
+    ));
+    assert!(mock_gl::calls_named("BlitFramebuffer").is_empty());
+}
+
+#[test]
+fn textured_elements_take_render_state_when_added() {
+    let gl = mock_gl::load();
+    let mut drawer = del_gl_core::drawer_mesh_tex::Drawer::new();
+    drawer.compile_shader(&gl).unwrap();
+    drawer.update_vertex(&gl, &vec![0., 0., 0., 1., 0., 0., 1., 1., 0.], 3);
+    let state = RenderState::default().cull_face(Some(gl::BACK));
+    let id_tri = drawer.add_element_with_state(&gl, gl::TRIANGLES, &[0u32, 1, 2], None, state);
+    let id_pnt = drawer.add_element(&gl, gl::POINTS, &vec![0u32], Some([1.0, 0.0, 0.0]));
+    assert_ne!(id_tri, id_pnt);
+    assert_eq!(*drawer.render_state_mut(id_tri).unwrap(), state);
+    mock_gl::take_calls();
+    drawer.draw(&gl, &IDENTITY, &IDENTITY);
+    assert_eq!(mock_gl::calls_named("CullFace")[0].args, [gl::BACK as i64]);
+    assert!(drawer.remove_element(id_tri));
+    mock_gl::take_calls();
+    drawer.draw(&gl, &IDENTITY, &IDENTITY);
+    assert_eq!(mock_gl::calls_named("DrawElements").len(), 1);
+    assert!(mock_gl::calls_named("CullFace").is_empty());
+}
+
+#[test]
+fn unpack_alignment_is_restored_after_texture_upload() {
+    let gl = mock_gl::load();
+    let (mut drawer, [id_tri, _]) = quad_drawer(&gl);
+    mock_gl::take_calls();
+    drawer.update_color_per_element(&gl, id_tri, &[1.0; 6], 3);
+    let alignments: Vec<Vec<i64>> = mock_gl::calls_named("PixelStorei")
+        .into_iter()
+        .map(|c| c.args)
+        .collect();
+    let unpack = gl::UNPACK_ALIGNMENT as i64;
+    assert_eq!(alignments, [vec![unpack, 1], vec![unpack, 4]]);
+}
+
+#[test]
+fn integer_attributes_are_bound_without_conversion() {
+    let gl = mock_gl::load();
+    let vs = b"in vec3 position;\nin ivec2 label;\nin vec4 color;\nvoid main() {}\n";
+    let fs = b"out vec4 FragColor;\nvoid main() {}\n";
+    let mut program =
+        ShaderProgram::new(ShaderSource::from_bytes(vs), ShaderSource::from_bytes(fs));
+    program.compile(&gl).unwrap();
+    mock_gl::take_calls();
+    VertexLayout::new(24)
+        .with_float("position", 3, 0)
+        .with_attribute(VertexAttribute::new("label", 2, gl::INT, 12))
+        .with_attribute(VertexAttribute::new("color", 4, gl::UNSIGNED_BYTE, 20).normalized(true))
+        .bind(&gl, &program);
+    let locations = |name: &str| -> Vec<i64> {
+        mock_gl::calls_named(name)
+            .into_iter()
+            .map(|c| c.args[0])
+            .collect()
+    };
+    let loc = |name: &str| program.attrib_location(name) as i64;
+    assert_eq!(locations("VertexAttribIPointer"), [loc("label")]);
+    assert_eq!(
+        locations("VertexAttribPointer"),
+        [loc("position"), loc("color")]
+    );
+}
+
+#[test]
+fn divisor_is_reset_when_location_is_rebound() {
+    let gl = mock_gl::load();
+    let vs = b"in vec3 position;\nvoid main() {}\n";
+    let fs = b"out vec4 FragColor;\nvoid main() {}\n";
+    let mut program =
+        ShaderProgram::new(ShaderSource::from_bytes(vs), ShaderSource::from_bytes(fs));
+    program.compile(&gl).unwrap();
+    let divisors = |layout: VertexLayout| -> Vec<i64> {
+        mock_gl::take_calls();
+        layout.bind(&gl, &program);
+        mock_gl::calls_named("VertexAttribDivisor")
+            .into_iter()
+            .map(|c| c.args[1])
+            .collect()
+    };
+    let instanced = VertexAttribute::new("position", 3, gl::FLOAT, 0).divisor(1);
+    assert_eq!(
+        divisors(VertexLayout::new(12).with_attribute(instanced)),
+        [1]
+    );
+    assert_eq!(
+        divisors(VertexLayout::new(12).with_float("position", 3, 0)),
+        [0]
+    );
+}
+
+#[test]
+fn uniforms_removed_by_defines_are_still_declared() {
+    let gl = mock_gl::load();
+    let vs = b"void main() {}\n";
+    let fs = b"#ifdef LIGHTING\nuniform vec3 light_dirs[4];\n#endif\nuniform vec3 color;\nvoid main() {}\n";
+    let mut program =
+        ShaderProgram::new(ShaderSource::from_bytes(vs), ShaderSource::from_bytes(fs));
+    program.compile(&gl).unwrap();
+    assert_eq!(program.uniform_location("light_dirs"), -1);
+    assert!(program.declares_uniform("light_dirs"));
+    assert!(program.declares_uniform("color"));
+    assert!(!program.declares_uniform("colour"));
+}
+
+#[test]
+fn unsupported_shader_type_is_an_error() {
+    let gl = mock_gl::load();
+    let err = unsafe { del_gl_core::create_shader(&gl, gl::COMPUTE_SHADER, b"void main() {}") }
+        .unwrap_err();
+    assert_eq!(
+        err.stage,
+        del_gl_core::ShaderStage::Unsupported(gl::COMPUTE_SHADER)
+    );
+    assert!(mock_gl::calls_named("CreateShader").is_empty());
+}
+
+#[test]
+fn colormap_of_another_size_is_resampled_to_compiled_table() {
+    let gl = mock_gl::load();
+    let mut drawer = del_gl_core::drawer_mesh_colormap::Drawer::new();
+    drawer.compile_shader(&gl).unwrap();
+    drawer.update_vertex(&gl, &vec![0., 0., 0., 1., 0., 0., 0., 1., 0.], 3);
+    drawer.update_value(&gl, &vec![0., 0.5, 1.]);
+    drawer.add_element(&gl, gl::TRIANGLES, &vec![0u32, 1, 2]);
+    let num_uploaded = |drawer: &del_gl_core::drawer_mesh_colormap::Drawer| {
+        mock_gl::take_calls();
+        drawer.draw(&gl, &IDENTITY, &IDENTITY);
+        mock_gl::calls_named("Uniform3fv")[0].args[1]
+    };
+    assert_eq!(
+        num_uploaded(&drawer),
+        del_gl_core::colormap::NUM_STOP as i64
+    );
+    drawer.color_map = vec![[0.0, 0.0, 1.0], [1.0, 0.0, 0.0]];
+    assert_eq!(
+        num_uploaded(&drawer),
+        del_gl_core::colormap::NUM_STOP as i64
+    );
+    drawer.compile_shader(&gl).unwrap();
+    assert_eq!(num_uploaded(&drawer), 2);
+}
+
+#[test]
+fn vertex_normals_of_triangles() {
+    // triangles on the xy and yz planes sharing the edge on the y-axis
+    let vtx2xyz = [0., 0., 0., 0., 1., 0., 1., 0., 0., 0., 0., 1.];
+    let tri2vtx = [0usize, 2, 1, 0, 1, 3];
+    let vtx2nrm = del_gl_core::lighting::vtx2normal_from_triangles(&tri2vtx, &vtx2xyz);
+    let s = std::f32::consts::FRAC_1_SQRT_2;
+    let expected = [s, 0., s, s, 0., s, 0., 0., 1., 1., 0., 0.];
+    for (a, b) in vtx2nrm.iter().zip(expected) {
+        assert!((a - b).abs() < 1.0e-6, "{:?}", vtx2nrm);
+    }
+}
+
+#[test]
+fn update_vertex_of_same_size_does_not_reallocate() {
+    let gl = mock_gl::load();
+    let (mut drawer, _) = quad_drawer(&gl);
+    mock_gl::take_calls();
+    drawer.update_vertex(&gl, &vec![0.5; 12], 3);
+    assert!(mock_gl::calls_named("BufferData").is_empty());
+    assert_eq!(mock_gl::calls_named("BufferSubData").len(), 1);
+    assert_eq!(mock_gl::num_live(ObjectKind::Buffer), 3);
+}
+
+#[test]
+fn resources_are_deleted_on_drop() {
+    let gl = mock_gl::load();
+    let (drawer, _) = quad_drawer(&gl);
+    // a vertex buffer and two element buffers
+    assert_eq!(mock_gl::num_live(ObjectKind::Buffer), 3);
+    assert_eq!(mock_gl::num_live(ObjectKind::VertexArray), 1);
+    assert_eq!(mock_gl::num_live(ObjectKind::Program), 1);
+    // the shaders are deleted after linking
+    assert_eq!(mock_gl::num_live(ObjectKind::Shader), 0);
+    drop(drawer);
+    for kind in [
+        ObjectKind::Buffer,
+        ObjectKind::VertexArray,
+        ObjectKind::Program,
+    ] {
+        assert_eq!(mock_gl::num_live(kind), 0, "{:?} is not deleted", kind);
+    }
+}
@@ -0,0 +1,69 @@
+//! tests of the `#include` expansion and the dialect translation of `shader_preprocessor`
+
+use del_gl_core::shader_preprocessor::{GlslDialect, Preprocessor};
+use del_gl_core::ShaderStage;
+
+#[test]
+fn line_directives_keep_line_numbers_of_source_and_snippets() {
+    let mut pp = Preprocessor::new();
+    pp.define("N", 3);
+    pp.register_snippet("a", "float a() {\n    return 1.0;\n}");
+    let src = "#include \"a\"\n#include \"a\"\nvoid main() {}\n";
+    let res = pp
+        .process(ShaderStage::Vertex, src, GlslDialect::Glsl330)
+        .unwrap();
+    let lines: Vec<&str> = res.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "#version 330",
+            "#define N 3",
+            "#line 1 0",
+            "#line 1 1",
+            "float a() {",
+            "    return 1.0;",
+            "}",
+            "#line 2 0",
+            "", // included only once
+            "void main() {}",
+        ]
+    );
+    // the next line of `#line N` is numbered N + 1 before GLSL 3.30
+    let res = pp
+        .process(ShaderStage::Vertex, src, GlslDialect::Glsl120)
+        .unwrap();
+    assert!(res.contains("#line 0 0\n#line 0 1\nfloat a() {"));
+    assert!(res.contains("}\n#line 1 0\n\nvoid main() {}"));
+}
+
+#[test]
+fn legacy_translation_skips_comments_and_local_variables() {
+    let src = "\
+layout (location = 0) out vec4 FragColor; // out color
+in\tvec2 tex; /* in, out
+out texture(s) */
+uniform sampler2D texture_map;
+void main() {
+    vec4 c = texture(texture_map, tex); // texture( in comment
+    FragColor = c;
+}
+";
+    let pp = Preprocessor::new();
+    let res = pp
+        .process(ShaderStage::Fragment, src, GlslDialect::Glsl120)
+        .unwrap();
+    let lines: Vec<&str> = res.lines().skip(2).collect();
+    assert_eq!(
+        lines,
+        [
+            "#define FragColor gl_FragColor // out color",
+            "varying vec2 tex; /* in, out",
+            "out texture(s) */",
+            "uniform sampler2D texture_map;",
+            "void main() {",
+            "    vec4 c = texture2D(texture_map, tex); // texture( in comment",
+            "    FragColor = c;",
+            "}",
+        ]
+    );
+}
@@ -0,0 +1,123 @@
+//! `#[derive(Vertex)]` for `del_gl_core::vertex_layout::Vertex`.
+//!
+//! ```ignore
+//! #[derive(Clone, Copy, Vertex)]
+//! #[repr(C)]
+//! struct Vertex2 {
+//!     position: [f32; 2],
+//!     #[vertex(name = "color", normalized)]
+//!     rgba: [u8; 4],
+//! }
+//! ```
+//! Each field is bound to the attribute of the same name unless `name` is given.
+//! `divisor = N` makes the attribute per-instance.
+
+use proc_macro::TokenStream;
+use quote::quote;
+
+struct FieldOption {
+    name: Option<String>,
+    normalized: bool,
+    divisor: u32,
+}
+
+fn parse_field_option(field: &syn::Field) -> syn::Result<FieldOption> {
+    let mut option = FieldOption {
+        name: None,
+        normalized: false,
+        divisor: 0,
+    };
+    for attr in &field.attrs {
+        if !attr.path().is_ident("vertex") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                let s: syn::LitStr = meta.value()?.parse()?;
+                option.name = Some(s.value());
+            } else if meta.path.is_ident("normalized") {
+                option.normalized = true;
+            } else if meta.path.is_ident("divisor") {
+                let n: syn::LitInt = meta.value()?.parse()?;
+                option.divisor = n.base10_parse()?;
+            } else {
+                return Err(meta.error("expected `name`, `normalized` or `divisor`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(option)
+}
+
+fn is_repr_c(input: &syn::DeriveInput) -> bool {
+    input.attrs.iter().any(|attr| {
+        let mut is_c = false;
+        if attr.path().is_ident("repr") {
+            let _ = attr.parse_nested_meta(|meta| {
+                is_c |= meta.path.is_ident("C");
+                Ok(())
+            });
+        }
+        is_c
+    })
+}
+
+fn expand(input: syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let ident = &input.ident;
+    let syn::Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "`Vertex` can be derived only for struct",
+        ));
+    };
+    let syn::Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "`Vertex` needs a struct with named fields",
+        ));
+    };
+    if !is_repr_c(&input) {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "`Vertex` needs `#[repr(C)]` to fix the field offsets",
+        ));
+    }
+    let mut attributes = vec![];
+    for field in &fields.named {
+        let field_ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let option = parse_field_option(field)?;
+        let name = option.name.unwrap_or_else(|| field_ident.to_string());
+        let normalized = option.normalized;
+        let divisor = option.divisor;
+        attributes.push(quote! {
+            .with_attribute(
+                ::del_gl_core::vertex_layout::VertexAttribute::new(
+                    #name,
+                    <#ty as ::del_gl_core::vertex_layout::VertexAttributeValue>::NUM_COMPONENT,
+                    <#ty as ::del_gl_core::vertex_layout::VertexAttributeValue>::TYPE,
+                    ::std::mem::offset_of!(Self, #field_ident),
+                )
+                .normalized(#normalized)
+                .divisor(#divisor),
+            )
+        });
+    }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::del_gl_core::vertex_layout::Vertex for #ident #ty_generics #where_clause {
+            fn layout() -> ::del_gl_core::vertex_layout::VertexLayout {
+                ::del_gl_core::vertex_layout::VertexLayout::new(::std::mem::size_of::<Self>())
+                    #(#attributes)*
+            }
+        }
+    })
+}
+
+#[proc_macro_derive(Vertex, attributes(vertex))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
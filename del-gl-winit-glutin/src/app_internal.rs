@@ -42,6 +42,7 @@ pub struct AppInternal {
     display_builder: DisplayBuilder,
     pub exit_state: Result<(), Box<dyn Error>>,
     not_current_gl_context: Option<NotCurrentContext>,
+    debug: bool,
     // NOTE: `AppState` carries the `Window`, thus it should be dropped after everything else.
     pub state: Option<AppState>,
 }
@@ -53,10 +54,22 @@ impl AppInternal {
             display_builder,
             exit_state: Ok(()),
             not_current_gl_context: None,
+            debug: false,
             state: None,
         }
     }
 
+    /// request a debug context. The viewers then report the GL errors and driver
+    /// messages through the `log` crate (see `del_gl_core::debug`)
+    pub fn with_debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    pub fn is_debug(&self) -> bool {
+        self.debug
+    }
+
     pub fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) -> Option<AppState> {
         let (mut window, gl_config) = match self.display_builder.clone().build(
             event_loop,
@@ -83,17 +96,21 @@ impl AppInternal {
         let gl_display = gl_config.display();
 
         // The context creation part.
-        let context_attributes = ContextAttributesBuilder::new().build(raw_window_handle);
+        let context_attributes = ContextAttributesBuilder::new()
+            .with_debug(self.debug)
+            .build(raw_window_handle);
 
         // Since glutin by default tries to create OpenGL core context, which may not be
         // present we should try gles.
         let fallback_context_attributes = ContextAttributesBuilder::new()
+            .with_debug(self.debug)
             .with_context_api(ContextApi::Gles(None))
             .build(raw_window_handle);
 
         // There are also some old devices that support neither modern OpenGL nor GLES.
         // To support these we can try and create a 2.1 context.
         let legacy_context_attributes = ContextAttributesBuilder::new()
+            .with_debug(self.debug)
             .with_context_api(ContextApi::OpenGl(Some(Version::new(2, 1))))
             .build(raw_window_handle);
 
@@ -0,0 +1,144 @@
+//! GL context without a window for batch rendering on a machine without display.
+//! The context is created on an EGL device (e.g., Mesa llvmpipe) and made current
+//! without a surface, or with a small pbuffer surface if the surfaceless context is
+//! not supported. The frames are rendered into an offscreen framebuffer and read back.
+
+use glutin::api::egl;
+use glutin::config::{ConfigSurfaceTypes, ConfigTemplateBuilder};
+use glutin::context::{ContextApi, ContextAttributesBuilder, Version};
+use glutin::display::GetDisplayExtensions;
+use glutin::prelude::*;
+use glutin::surface::{PbufferSurface, SurfaceAttributesBuilder};
+use std::error::Error;
+use std::num::NonZeroU32;
+use std::rc::Rc;
+//
+use del_gl_core::framebuffer::{DepthAttachment, Framebuffer, FramebufferDesc};
+use del_gl_core::gl;
+use del_gl_core::texture::TextureFormat;
+
+pub struct Headless {
+    pub gl: Rc<gl::Gl>,
+    /// RGBA8 color and depth target of the size given to `new`
+    pub framebuffer: Framebuffer,
+    // NOTE: the GL objects above should be deleted while the context is current,
+    // so the surface and context are dropped last.
+    _surface: Option<egl::surface::Surface<PbufferSurface>>,
+    _context: egl::context::PossiblyCurrentContext,
+}
+
+impl Headless {
+    /// create a context on the first EGL device that works and an offscreen target
+    /// of `width`x`height` pixels. The context is current on return
+    pub fn new(width: usize, height: usize) -> Result<Self, Box<dyn Error>> {
+        let mut last_error: Box<dyn Error> = "no EGL device is found".into();
+        for device in egl::device::Device::query_devices()? {
+            let display = match unsafe { egl::display::Display::with_device(&device, None) } {
+                Ok(display) => display,
+                Err(e) => {
+                    last_error = Box::new(e);
+                    continue;
+                }
+            };
+            match Self::with_display(display, width, height) {
+                Ok(headless) => return Ok(headless),
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+
+    fn with_display(
+        display: egl::display::Display,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, Box<dyn Error>> {
+        let template = ConfigTemplateBuilder::new()
+            .with_alpha_size(8)
+            .with_surface_type(ConfigSurfaceTypes::PBUFFER)
+            .build();
+        let config = unsafe { display.find_configs(template)? }
+            .next()
+            .ok_or("no EGL config supporting pbuffer")?;
+        // same fallback order as `AppInternal::resumed`
+        let context_attributes = [
+            ContextAttributesBuilder::new().build(None),
+            ContextAttributesBuilder::new()
+                .with_context_api(ContextApi::Gles(None))
+                .build(None),
+            ContextAttributesBuilder::new()
+                .with_context_api(ContextApi::OpenGl(Some(Version::new(2, 1))))
+                .build(None),
+        ];
+        let not_current_context = context_attributes
+            .iter()
+            .find_map(|attributes| unsafe { display.create_context(&config, attributes).ok() })
+            .ok_or("failed to create context")?;
+        let (context, surface) = if display.extensions().contains("EGL_KHR_surfaceless_context") {
+            (not_current_context.make_current_surfaceless()?, None)
+        } else {
+            let attributes = SurfaceAttributesBuilder::<PbufferSurface>::new()
+                .build(NonZeroU32::MIN, NonZeroU32::MIN);
+            let surface = unsafe { display.create_pbuffer_surface(&config, &attributes)? };
+            (not_current_context.make_current(&surface)?, Some(surface))
+        };
+        let gl = Rc::new(gl::Gl::load_with(|symbol| {
+            let symbol = std::ffi::CString::new(symbol).unwrap();
+            display.get_proc_address(symbol.as_c_str()).cast()
+        }));
+        let desc = FramebufferDesc::new(width, height)
+            .with_color(TextureFormat::Rgba8)
+            .with_depth(DepthAttachment::Renderbuffer(TextureFormat::Depth24));
+        let framebuffer = Framebuffer::new(&gl, &desc)?;
+        framebuffer.bind();
+        unsafe {
+            gl.Enable(gl::DEPTH_TEST);
+        }
+        Ok(Headless {
+            gl,
+            framebuffer,
+            _surface: surface,
+            _context: context,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.framebuffer.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.framebuffer.height()
+    }
+
+    /// change the size of the offscreen target
+    pub fn resize(&mut self, width: usize, height: usize) -> Result<(), Box<dyn Error>> {
+        self.framebuffer.resize(width, height)?;
+        self.framebuffer.bind();
+        Ok(())
+    }
+
+    /// clear the target with the same color as `Viewer3d` and draw `renderer`.
+    /// Returns the RGB image in top-down row order
+    pub fn render(
+        &mut self,
+        renderer: &mut dyn crate::viewer3d_for_gl_renderer::GlRenderer,
+        cam_model: &[f32; 16],
+        cam_projection: &[f32; 16],
+    ) -> Vec<u8> {
+        let gl = &self.gl;
+        self.framebuffer.bind();
+        unsafe {
+            gl.ClearColor(0.3, 0.3, 0.3, 1.0);
+            gl.Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+        renderer.draw(gl, cam_model, cam_projection);
+        unsafe {
+            gl.Finish();
+        }
+        del_gl_core::readback::read_pixels_rgb8(
+            gl,
+            Some(&self.framebuffer),
+            (self.width(), self.height()),
+        )
+    }
+}
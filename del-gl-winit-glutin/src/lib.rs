@@ -1,4 +1,6 @@
 pub mod app_internal;
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+pub mod headless;
 pub mod viewer3d_for_image_generator;
 pub mod viewer3d_for_gl_renderer;
 
@@ -1,13 +1,17 @@
 use del_gl_core::gl;
+use std::rc::Rc;
 
+/// the GL objects are created with the `Rc` of the function table shared by the viewer
 pub trait GlRenderer {
-    fn initialize(&mut self, gl: &gl::Gl);
-    fn draw(&mut self, gl: &gl::Gl, cam_model: &[f32; 16], cam_projection: &[f32; 16]);
+    fn initialize(&mut self, gl: &Rc<gl::Gl>);
+    fn draw(&mut self, gl: &Rc<gl::Gl>, cam_model: &[f32; 16], cam_projection: &[f32; 16]);
 }
 
 pub struct Viewer3d {
     pub appi: crate::app_internal::AppInternal,
     pub renderer: Box<dyn GlRenderer>,
+    /// loaded when the context is created for the first time
+    gl: Option<Rc<gl::Gl>>,
     pub view_rot: del_geo_core::view_rotation::Trackball<f32>,
     pub view_prj: del_geo_core::view_projection::Perspective<f32>,
     pub ui_state: del_gl_core::view_ui_state::UiState,
@@ -24,6 +28,7 @@ impl Viewer3d {
         Self {
             appi: crate::app_internal::AppInternal::new(template, display_builder),
             renderer: vt,
+            gl: None,
             ui_state: del_gl_core::view_ui_state::UiState::new(),
             view_rot: del_geo_core::view_rotation::Trackball::new(),
             view_prj: del_geo_core::view_projection::Perspective {
@@ -52,15 +57,19 @@ impl winit::application::ApplicationHandler for Viewer3d {
         // self.renderer.get_or_insert_with(|| {
         {
             let gl_display = &app_state.gl_context.display();
-            let gl = del_gl_core::gl::Gl::load_with(|symbol| {
+            let gl = Rc::new(del_gl_core::gl::Gl::load_with(|symbol| {
                 let symbol = std::ffi::CString::new(symbol).unwrap();
                 use glutin::display::GlDisplay;
                 gl_display.get_proc_address(symbol.as_c_str()).cast()
-            });
+            }));
+            if self.appi.is_debug() {
+                del_gl_core::debug::enable(&gl, del_gl_core::debug::Severity::Low);
+            }
             unsafe {
                 gl.Enable(gl::DEPTH_TEST);
             }
             self.renderer.initialize(&gl);
+            self.gl = Some(gl);
         }
         assert!(self.appi.state.replace(app_state).is_none());
     }
@@ -79,11 +88,11 @@ impl winit::application::ApplicationHandler for Viewer3d {
                 // Notable platforms here are Wayland and macOS, other don't require it
                 // and the function is no-op, but it's wise to resize it for portability
                 // reasons.
-                if let Some(crate::app_internal::AppState {
+                if let (Some(crate::app_internal::AppState {
                                 gl_context,
                                 gl_surface,
                                 window: _,
-                            }) = self.appi.state.as_ref()
+                            }), Some(gl)) = (self.appi.state.as_ref(), self.gl.as_ref())
                 {
                     gl_surface.resize(
                         gl_context,
@@ -92,13 +101,6 @@ impl winit::application::ApplicationHandler for Viewer3d {
                     );
                     // let renderer = self.renderer.as_ref().unwrap();
                     // renderer.resize(size.width as i32, size.height as i32);
-                    use glutin::display::GetGlDisplay;
-                    let gl_display = &gl_context.display();
-                    let gl = del_gl_core::gl::Gl::load_with(|symbol| {
-                        let symbol = std::ffi::CString::new(symbol).unwrap();
-                        use glutin::display::GlDisplay;
-                        gl_display.get_proc_address(symbol.as_c_str()).cast()
-                    });
                     unsafe {
                         gl.Viewport(0, 0, size.width as i32, size.height as i32);
                     }
@@ -130,11 +132,11 @@ impl winit::application::ApplicationHandler for Viewer3d {
 
     fn about_to_wait(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop) {
         use glutin::prelude::GlSurface;
-        if let Some(crate::app_internal::AppState {
+        if let (Some(crate::app_internal::AppState {
                         gl_context,
                         gl_surface,
                         window,
-                    }) = self.appi.state.as_ref()
+                    }), Some(gl)) = (self.appi.state.as_ref(), self.gl.as_ref())
         {
             let img_shape = { (window.inner_size().width, window.inner_size().height) };
             let cam_model = self.view_rot.mat4_col_major();
@@ -143,19 +145,13 @@ impl winit::application::ApplicationHandler for Viewer3d {
                 .mat4_col_major(img_shape.0 as f32 / img_shape.1 as f32);
             use std::ops::DerefMut;
             let renderer = self.renderer.deref_mut();
-            use glutin::display::GetGlDisplay;
-            let gl_display = &gl_context.display();
-            let gl = del_gl_core::gl::Gl::load_with(|symbol| {
-                let symbol = std::ffi::CString::new(symbol).unwrap();
-                use glutin::display::GlDisplay;
-                gl_display.get_proc_address(symbol.as_c_str()).cast()
-            });
             unsafe {
                 gl.ClearColor(0.3, 0.3, 0.3, 1.0);
                 gl.Clear(gl::COLOR_BUFFER_BIT);
                 gl.Clear(gl::DEPTH_BUFFER_BIT);
             }
-            renderer.draw(&gl, &cam_model, &cam_projection);
+            renderer.draw(gl, &cam_model, &cam_projection);
+            del_gl_core::debug::check_error(gl, "GlRenderer::draw");
             window.request_redraw();
             gl_surface.swap_buffers(gl_context).unwrap();
         }
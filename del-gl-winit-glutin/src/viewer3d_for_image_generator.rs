@@ -56,17 +56,24 @@ impl ApplicationHandler for Viewer3d {
         // The context needs to be current for the Renderer to set up shaders and
         // buffers. It also performs function loading, which needs a current context on
         // WGL.
-        self.renderer.get_or_insert_with(|| {
+        if self.renderer.is_none() {
             let gl_display = &app_state.gl_context.display();
-            let gl = gl::Gl::load_with(|symbol| {
+            let gl = std::rc::Rc::new(gl::Gl::load_with(|symbol| {
                 let symbol = std::ffi::CString::new(symbol).unwrap();
                 use glutin::display::GlDisplay;
                 gl_display.get_proc_address(symbol.as_c_str()).cast()
-            });
+            }));
+            if self.appi.is_debug() {
+                del_gl_core::debug::enable(&gl, del_gl_core::debug::Severity::Low);
+            }
             let mut render = del_gl_core::drawer_array_xyzuv::Drawer::new(gl);
-            render.init_gl();
-            render
-        });
+            if let Err(e) = render.init_gl() {
+                self.appi.exit_state = Err(Box::new(e));
+                event_loop.exit();
+                return;
+            }
+            self.renderer = Some(render);
+        }
         unsafe {
             //
             let Some(rndr) = &self.renderer else {
@@ -84,7 +91,7 @@ impl ApplicationHandler for Viewer3d {
                     1.0, 1.0, 1., 0.,
                     -1.0, 1.0, 0., 0.
                 ];
-                gl.BindBuffer(gl::ARRAY_BUFFER, rndr.vbo);
+                rndr.vbo.bind();
                 gl.BufferData(
                     gl::ARRAY_BUFFER,
                     (VERTEX_DATA.len() * std::mem::size_of::<f32>()) as gl::types::GLsizeiptr,
@@ -180,25 +187,16 @@ impl ApplicationHandler for Viewer3d {
                 .compute_image(img_shape, &cam_projection, &cam_model);
             assert_eq!(img_data.len(), img_shape.0 * img_shape.1 * 3);
             //println!("{:?}",img.color());
-            let Some(ref rndr) = self.renderer else {
+            let Some(ref mut rndr) = self.renderer else {
                 panic!();
             };
-            let gl = &rndr.gl;
-            unsafe {
-                gl.BindTexture(gl::TEXTURE_2D, rndr.id_tex);
-                gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
-                gl.TexImage2D(
-                    gl::TEXTURE_2D,
-                    0,
-                    gl::RGB.try_into().unwrap(),
-                    img_shape.0.try_into().unwrap(),
-                    img_shape.1.try_into().unwrap(),
-                    0,
-                    gl::RGB,
-                    gl::UNSIGNED_BYTE,
-                    img_data.as_ptr() as *const _,
-                );
-                gl.GenerateMipmap(gl::TEXTURE_2D);
+            use del_gl_core::texture::{TextureDesc, TextureFormat};
+            let desc =
+                TextureDesc::new(img_shape.0, img_shape.1, TextureFormat::Rgb8).mipmap(true);
+            if rndr.tex.desc() == Some(&desc) {
+                rndr.tex.set_sub_image(0, 0, img_shape.0, img_shape.1, &img_data);
+            } else {
+                rndr.tex.set_image(&desc, Some(&img_data));
             }
             //
             let renderer = self.renderer.as_ref().unwrap();
@@ -0,0 +1,559 @@
+//! golden-image regression tests of the drawers.
+//!
+//! Each drawer renders `asset/spot_triangulated.obj` (or a small primitive for the
+//! drawers that can not draw a mesh) in a headless context from fixed camera matrices,
+//! and the result is compared with `tests/golden/<name>.png`.
+//! The tests need an EGL device, so they are ignored by default and fail without a device
+//! when run with `cargo test -p del-gl-winit-glutin --test golden -- --include-ignored`.
+//!
+//! Set `DEL_GL_BLESS=1` to (re)write the reference images. On failure, the rendered
+//! image and the difference image are written in `target/tmp/golden/`.
+
+use del_gl_core::drawer_mesh::ColorSource;
+use del_gl_core::gl;
+use del_gl_core::render_state::{Blend, RenderState};
+use del_gl_core::texture::TextureFormat;
+use del_gl_winit_glutin::headless::Headless;
+use del_gl_winit_glutin::viewer3d_for_gl_renderer::GlRenderer;
+use std::rc::Rc;
+
+const WIDTH: usize = 256;
+const HEIGHT: usize = 256;
+/// maximum difference of a channel regarded as the same pixel
+const TOLERANCE: u8 = 16;
+/// ratio of the pixels allowed to differ. Rasterization of the edges may vary
+/// slightly among the driver versions
+const MAX_MISMATCH_RATIO: f64 = 0.005;
+
+/// model-view matrix moving the center of the model to the origin and
+/// rotating it 150 degrees around the y-axis to look at its face
+#[rustfmt::skip]
+const CAM_MODEL: [f32; 16] = [
+    -0.866, 0.0, -0.5, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.5, 0.0, -0.866, 0.0,
+    -0.095, -0.1, 0.164, 1.0,
+];
+
+/// orthographic projection of the standard OpenGL convention (looking at -z)
+#[rustfmt::skip]
+const CAM_PROJECTION: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, -0.5, 0.0,
+    0.0, 0.0, 0.0, 1.0,
+];
+
+/// same projection for the drawers flipping the z coordinate (`Z_FLIP`)
+#[rustfmt::skip]
+const CAM_PROJECTION_Z_FLIP: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.0,
+    0.0, 0.0, 0.0, 1.0,
+];
+
+/// `GlRenderer` calling the closure in `draw`
+struct DrawFn<F>(F);
+
+impl<F: FnMut(&gl::Gl, &[f32; 16], &[f32; 16])> GlRenderer for DrawFn<F> {
+    fn initialize(&mut self, _gl: &Rc<gl::Gl>) {}
+
+    fn draw(&mut self, gl: &Rc<gl::Gl>, cam_model: &[f32; 16], cam_projection: &[f32; 16]) {
+        (self.0)(gl, cam_model, cam_projection)
+    }
+}
+
+fn asset_path(name: &str) -> String {
+    format!("{}/../asset/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn load_spot() -> (Vec<usize>, Vec<f32>) {
+    let mut obj = del_msh_core::io_obj::WavefrontObj::<usize, f32>::new();
+    obj.load(asset_path("spot_triangulated.obj").as_str())
+        .unwrap();
+    (obj.idx2vtx_xyz, obj.vtx2xyz)
+}
+
+fn headless() -> Headless {
+    Headless::new(WIDTH, HEIGHT).unwrap_or_else(|e| panic!("no headless context ({})", e))
+}
+
+fn save_rgb(path: &std::path::Path, img: &[u8]) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    image::save_buffer(
+        path,
+        img,
+        WIDTH as u32,
+        HEIGHT as u32,
+        image::ExtendedColorType::Rgb8,
+    )
+    .unwrap();
+}
+
+/// compare the RGB image `img` with the reference image `tests/golden/<name>.png`
+fn check_golden(name: &str, img: &[u8]) {
+    let path_golden =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("tests/golden/{}.png", name));
+    if std::env::var_os("DEL_GL_BLESS").is_some() {
+        save_rgb(&path_golden, img);
+        return;
+    }
+    let dir_out = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    let path_actual = dir_out.join(format!("{}_actual.png", name));
+    let golden = match image::open(&path_golden) {
+        Ok(golden) => golden.to_rgb8(),
+        Err(e) => {
+            save_rgb(&path_actual, img);
+            panic!(
+                "failed to open {:?} ({}). Run with DEL_GL_BLESS=1 to create it. The rendered image is {:?}",
+                path_golden, e, path_actual
+            );
+        }
+    };
+    assert_eq!(golden.dimensions(), (WIDTH as u32, HEIGHT as u32));
+    let mut diff = vec![0u8; img.len()];
+    let mut num_mismatch = 0;
+    for ((p0, p1), d) in img
+        .chunks(3)
+        .zip(golden.as_raw().chunks(3))
+        .zip(diff.chunks_mut(3))
+    {
+        let max_diff = (0..3).map(|i| p0[i].abs_diff(p1[i])).max().unwrap();
+        if max_diff > TOLERANCE {
+            num_mismatch += 1;
+            d.copy_from_slice(&[255, 0, 0]);
+        } else {
+            // darkened reference image to locate the mismatched pixels
+            d.copy_from_slice(&[p1[0] / 4, p1[1] / 4, p1[2] / 4]);
+        }
+    }
+    let ratio = num_mismatch as f64 / (WIDTH * HEIGHT) as f64;
+    if ratio > MAX_MISMATCH_RATIO {
+        let path_diff = dir_out.join(format!("{}_diff.png", name));
+        save_rgb(&path_actual, img);
+        save_rgb(&path_diff, &diff);
+        panic!(
+            "`{}` differs from the reference in {} pixels ({:.2}%). See {:?} and {:?}",
+            name,
+            num_mismatch,
+            ratio * 100.0,
+            path_actual,
+            path_diff
+        );
+    }
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_mesh() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    let (tri2vtx, vtx2xyz) = load_spot();
+    let edge2vtx = del_msh_core::edge2vtx::from_triangle_mesh(&tri2vtx, vtx2xyz.len() / 3);
+    let mut drawer = del_gl_core::drawer_mesh::Drawer::new();
+    drawer.compile_shader(&gl).unwrap();
+    drawer.update_vertex(&gl, &vtx2xyz, 3);
+    drawer.add_element(&gl, gl::TRIANGLES, &tri2vtx, [1.0, 0.0, 0.0]);
+    drawer.add_element(&gl, gl::LINES, &edge2vtx, [0.0, 0.0, 0.0]);
+    let img = headless.render(
+        &mut DrawFn(|gl: &gl::Gl, mv: &[f32; 16], prj: &[f32; 16]| drawer.draw(gl, mv, prj)),
+        &CAM_MODEL,
+        &CAM_PROJECTION,
+    );
+    check_golden("drawer_mesh", &img);
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_mesh_render_state() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    let (tri2vtx, vtx2xyz) = load_spot();
+    let edge2vtx = del_msh_core::edge2vtx::from_triangle_mesh(&tri2vtx, vtx2xyz.len() / 3);
+    let pnt2vtx: Vec<usize> = (0..vtx2xyz.len() / 3).step_by(7).collect();
+    let mut drawer = del_gl_core::drawer_mesh::Drawer::new();
+    drawer.compile_shader(&gl).unwrap();
+    drawer.update_vertex(&gl, &vtx2xyz, 3);
+    let i_tri = drawer.add_element(&gl, gl::TRIANGLES, &tri2vtx, [1.0, 0.0, 0.0]);
+    drawer.add_element(&gl, gl::LINES, &edge2vtx, [0.0, 0.0, 0.0]);
+    let i_pnt = drawer.add_element(&gl, gl::POINTS, &pnt2vtx, [1.0, 1.0, 0.0]);
+    *drawer.render_state_mut(i_tri).unwrap() = RenderState::default()
+        .polygon_offset(1.0, 1.0)
+        .cull_face(Some(gl::BACK));
+    *drawer.render_state_mut(i_pnt).unwrap() = RenderState::default().point_size(4.0);
+    let img = headless.render(
+        &mut DrawFn(|gl: &gl::Gl, mv: &[f32; 16], prj: &[f32; 16]| drawer.draw(gl, mv, prj)),
+        &CAM_MODEL,
+        &CAM_PROJECTION,
+    );
+    check_golden("drawer_mesh_render_state", &img);
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_mesh_vertex_color() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    let (tri2vtx, vtx2xyz) = load_spot();
+    // position in [-1, 1] mapped to RGB
+    let vtx2rgb: Vec<f32> = vtx2xyz
+        .iter()
+        .map(|&x| (x * 0.5 + 0.5).clamp(0.0, 1.0))
+        .collect();
+    let mut drawer = del_gl_core::drawer_mesh::Drawer::new();
+    drawer.compile_shader(&gl).unwrap();
+    drawer.update_vertex(&gl, &vtx2xyz, 3);
+    drawer.update_normal_from_triangles(&gl, &tri2vtx, &vtx2xyz);
+    drawer.update_color_per_vertex(&gl, &vtx2rgb, 3);
+    let id = drawer.add_element(&gl, gl::TRIANGLES, &tri2vtx, [1.0, 1.0, 1.0]);
+    drawer.set_color_source(id, ColorSource::Vertex);
+    let img = headless.render(
+        &mut DrawFn(|gl: &gl::Gl, mv: &[f32; 16], prj: &[f32; 16]| drawer.draw(gl, mv, prj)),
+        &CAM_MODEL,
+        &CAM_PROJECTION,
+    );
+    check_golden("drawer_mesh_vertex_color", &img);
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_mesh_element_color() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    let (tri2vtx, vtx2xyz) = load_spot();
+    // labels of the triangles in stripes along the x-axis
+    let tri2rgb: Vec<f32> = tri2vtx
+        .chunks(3)
+        .flat_map(|tri| {
+            let x = tri.iter().map(|&i| vtx2xyz[i * 3]).sum::<f32>() / 3.0;
+            match ((x + 1.0) * 8.0) as usize % 3 {
+                0 => [1.0, 0.2, 0.2],
+                1 => [0.2, 1.0, 0.2],
+                _ => [0.2, 0.2, 1.0],
+            }
+        })
+        .collect();
+    let mut drawer = del_gl_core::drawer_mesh::Drawer::new();
+    drawer.compile_shader(&gl).unwrap();
+    drawer.update_vertex(&gl, &vtx2xyz, 3);
+    drawer.update_normal_from_triangles(&gl, &tri2vtx, &vtx2xyz);
+    let id = drawer.add_element(&gl, gl::TRIANGLES, &tri2vtx, [1.0, 1.0, 1.0]);
+    drawer.update_color_per_element(&gl, id, &tri2rgb, 3);
+    let img = headless.render(
+        &mut DrawFn(|gl: &gl::Gl, mv: &[f32; 16], prj: &[f32; 16]| drawer.draw(gl, mv, prj)),
+        &CAM_MODEL,
+        &CAM_PROJECTION,
+    );
+    check_golden("drawer_mesh_element_color", &img);
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_mesh_oit() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    let (tri2vtx, vtx2xyz) = load_spot();
+    // opaque smaller copy seen through the semi-transparent outer surface
+    let vtx2xyz_inner: Vec<f32> = vtx2xyz.iter().map(|&x| x * 0.5).collect();
+    let mut inner = del_gl_core::drawer_mesh::Drawer::new();
+    inner.compile_shader(&gl).unwrap();
+    inner.update_vertex(&gl, &vtx2xyz_inner, 3);
+    inner.update_normal_from_triangles(&gl, &tri2vtx, &vtx2xyz_inner);
+    inner.add_element(&gl, gl::TRIANGLES, &tri2vtx, [1.0, 0.0, 0.0]);
+    let mut outer = del_gl_core::drawer_mesh::Drawer::new();
+    outer.compile_shader(&gl).unwrap();
+    outer.update_vertex(&gl, &vtx2xyz, 3);
+    outer.update_normal_from_triangles(&gl, &tri2vtx, &vtx2xyz);
+    let id = outer.add_element(&gl, gl::TRIANGLES, &tri2vtx, [0.8, 0.8, 0.8]);
+    outer.material_mut(id).unwrap().alpha = 0.35;
+    outer.render_state_mut(id).unwrap().blend = Some(Blend::WeightedOit);
+    let mut target =
+        del_gl_core::oit::Target::new(&gl, WIDTH, HEIGHT, TextureFormat::Depth24).unwrap();
+    let img = headless.render(
+        &mut DrawFn(|gl: &gl::Gl, mv: &[f32; 16], prj: &[f32; 16]| {
+            inner.draw(gl, mv, prj);
+            target.begin(gl).unwrap();
+            outer.draw_transparent(gl, mv, prj);
+            target.end(gl);
+        }),
+        &CAM_MODEL,
+        &CAM_PROJECTION,
+    );
+    check_golden("drawer_mesh_oit", &img);
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_mesh_lighting() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    let (tri2vtx, vtx2xyz) = load_spot();
+    // cut the mesh to show the back faces inside
+    let tri2vtx: Vec<usize> = tri2vtx
+        .chunks(3)
+        .filter(|tri| tri.iter().map(|&i| vtx2xyz[i * 3 + 2]).sum::<f32>() > -0.3)
+        .flatten()
+        .copied()
+        .collect();
+    let mut drawer = del_gl_core::drawer_mesh::Drawer::new();
+    drawer.compile_shader(&gl).unwrap();
+    drawer.update_vertex(&gl, &vtx2xyz, 3);
+    drawer.update_normal_from_triangles(&gl, &tri2vtx, &vtx2xyz);
+    drawer
+        .lights
+        .push(del_gl_core::lighting::DirectionalLight::new(
+            [0.0, 1.0, 0.0],
+            [0.2, 0.2, 0.5],
+        ));
+    let material = del_gl_core::lighting::Material::new([0.8, 0.8, 0.8]);
+    drawer.add_element_with_material(&gl, gl::TRIANGLES, &tri2vtx, material);
+    let img = headless.render(
+        &mut DrawFn(|gl: &gl::Gl, mv: &[f32; 16], prj: &[f32; 16]| drawer.draw(gl, mv, prj)),
+        &CAM_MODEL,
+        &CAM_PROJECTION,
+    );
+    check_golden("drawer_mesh_lighting", &img);
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_mesh_flat() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    let (tri2vtx, vtx2xyz) = load_spot();
+    // no normals. The shared vertices are shaded per face
+    let mut drawer = del_gl_core::drawer_mesh::Drawer::new();
+    drawer.flat_shading = true;
+    drawer.compile_shader(&gl).unwrap();
+    drawer.update_vertex(&gl, &vtx2xyz, 3);
+    let material = del_gl_core::lighting::Material::new([0.8, 0.8, 0.8]);
+    drawer.add_element_with_material(&gl, gl::TRIANGLES, &tri2vtx, material);
+    let img = headless.render(
+        &mut DrawFn(|gl: &gl::Gl, mv: &[f32; 16], prj: &[f32; 16]| drawer.draw(gl, mv, prj)),
+        &CAM_MODEL,
+        &CAM_PROJECTION,
+    );
+    check_golden("drawer_mesh_flat", &img);
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_mesh_wireframe() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    let (tri2vtx, vtx2xyz) = load_spot();
+    // cut the mesh to show that the edges on the back faces are hidden
+    let tri2vtx: Vec<usize> = tri2vtx
+        .chunks(3)
+        .filter(|tri| tri.iter().map(|&i| vtx2xyz[i * 3 + 2]).sum::<f32>() > -0.3)
+        .flatten()
+        .copied()
+        .collect();
+    let mut drawer = del_gl_core::drawer_mesh::Drawer::new();
+    drawer.compile_shader(&gl).unwrap();
+    drawer.update_vertex(&gl, &vtx2xyz, 3);
+    drawer.update_normal_from_triangles(&gl, &tri2vtx, &vtx2xyz);
+    let material = del_gl_core::lighting::Material::new([0.8, 0.8, 0.8]);
+    drawer.add_element_with_material(&gl, gl::TRIANGLES, &tri2vtx, material);
+    let wireframe = del_gl_core::drawer_mesh::Wireframe::new([0.0, 0.0, 0.3])
+        .width(1.5)
+        .hide_back(true);
+    drawer.set_wireframe(&gl, Some(wireframe)).unwrap();
+    let img = headless.render(
+        &mut DrawFn(|gl: &gl::Gl, mv: &[f32; 16], prj: &[f32; 16]| drawer.draw(gl, mv, prj)),
+        &CAM_MODEL,
+        &CAM_PROJECTION,
+    );
+    check_golden("drawer_mesh_wireframe", &img);
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_mesh_colormap() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    let (tri2vtx, vtx2xyz) = load_spot();
+    // height of the vertices
+    let vtx2val: Vec<f32> = vtx2xyz.chunks(3).map(|xyz| xyz[1]).collect();
+    let mut drawer = del_gl_core::drawer_mesh_colormap::Drawer::new();
+    drawer.color_map = vec![[0.0, 0.0, 1.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]];
+    drawer.val_min = -0.7;
+    drawer.val_max = 1.0;
+    drawer.compile_shader(&gl).unwrap();
+    drawer.update_vertex(&gl, &vtx2xyz, 3);
+    drawer.update_value(&gl, &vtx2val);
+    drawer.add_element(&gl, gl::TRIANGLES, &tri2vtx);
+    let img = headless.render(
+        &mut DrawFn(|gl: &gl::Gl, mv: &[f32; 16], prj: &[f32; 16]| drawer.draw(gl, mv, prj)),
+        &CAM_MODEL,
+        &CAM_PROJECTION_Z_FLIP,
+    );
+    check_golden("drawer_mesh_colormap", &img);
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_mesh_colormap_flat() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    let (tri2vtx, vtx2xyz) = load_spot();
+    let vtx2val: Vec<f32> = vtx2xyz.chunks(3).map(|xyz| xyz[1]).collect();
+    let mut drawer = del_gl_core::drawer_mesh_colormap::Drawer::new();
+    drawer.color_map = vec![[0.0, 0.0, 1.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]];
+    drawer.val_min = -0.7;
+    drawer.val_max = 1.0;
+    drawer.flat_shading = true;
+    drawer.compile_shader(&gl).unwrap();
+    drawer.update_vertex(&gl, &vtx2xyz, 3);
+    drawer.update_value(&gl, &vtx2val);
+    drawer.add_element(&gl, gl::TRIANGLES, &tri2vtx);
+    let img = headless.render(
+        &mut DrawFn(|gl: &gl::Gl, mv: &[f32; 16], prj: &[f32; 16]| drawer.draw(gl, mv, prj)),
+        &CAM_MODEL,
+        &CAM_PROJECTION_Z_FLIP,
+    );
+    check_golden("drawer_mesh_colormap_flat", &img);
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_mesh_colormap_preset() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    let (tri2vtx, vtx2xyz) = load_spot();
+    let vtx2val: Vec<f32> = vtx2xyz.chunks(3).map(|xyz| xyz[1]).collect();
+    let mut drawer = del_gl_core::drawer_mesh_colormap::Drawer::new();
+    drawer.color_map = del_gl_core::colormap::from_name("turbo_r").unwrap();
+    drawer.val_min = -0.7;
+    drawer.val_max = 1.0;
+    drawer.compile_shader(&gl).unwrap();
+    drawer.update_vertex(&gl, &vtx2xyz, 3);
+    drawer.update_value(&gl, &vtx2val);
+    drawer.add_element(&gl, gl::TRIANGLES, &tri2vtx);
+    let img = headless.render(
+        &mut DrawFn(|gl: &gl::Gl, mv: &[f32; 16], prj: &[f32; 16]| drawer.draw(gl, mv, prj)),
+        &CAM_MODEL,
+        &CAM_PROJECTION_Z_FLIP,
+    );
+    check_golden("drawer_mesh_colormap_preset", &img);
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_mesh_tex() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    let (tri2vtx, vtx2xyz, vtx2uv) = {
+        let mut obj = del_msh_core::io_obj::WavefrontObj::<usize, f32>::new();
+        obj.load(asset_path("spot_triangulated.obj").as_str())
+            .unwrap();
+        obj.unified_xyz_uv_as_trimesh()
+    };
+    let tex = del_gl_core::texture::Texture::from_path(&gl, asset_path("spot_texture.png"), false)
+        .unwrap();
+    let mut drawer = del_gl_core::drawer_mesh_tex::Drawer::new();
+    drawer.compile_shader(&gl).unwrap();
+    drawer.update_vertex(&gl, &vtx2xyz, 3);
+    drawer.set_texture_uv(&gl, &vtx2uv);
+    drawer.add_element(&gl, gl::TRIANGLES, &tri2vtx, None);
+    let img = headless.render(
+        &mut DrawFn(|gl: &gl::Gl, mv: &[f32; 16], prj: &[f32; 16]| {
+            unsafe {
+                gl.ActiveTexture(gl::TEXTURE0);
+            }
+            tex.bind();
+            drawer.draw(gl, mv, prj)
+        }),
+        &CAM_MODEL,
+        &CAM_PROJECTION_Z_FLIP,
+    );
+    check_golden("drawer_mesh_tex", &img);
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_array() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    let (tri2vtx, vtx2xyz) = load_spot();
+    // triangle soup
+    let vtx2xyz: Vec<f32> = tri2vtx
+        .iter()
+        .flat_map(|&ivtx| {
+            [
+                vtx2xyz[ivtx * 3],
+                vtx2xyz[ivtx * 3 + 1],
+                vtx2xyz[ivtx * 3 + 2],
+            ]
+        })
+        .collect();
+    let mut drawer = del_gl_core::drawer_array::Drawer::new(gl::TRIANGLES, [0.0, 0.5, 1.0]);
+    drawer.compile_shader(&gl).unwrap();
+    drawer.initialize(&gl, &vtx2xyz);
+    drawer.elem_size = tri2vtx.len();
+    let img = headless.render(
+        &mut DrawFn(|gl: &gl::Gl, mv: &[f32; 16], prj: &[f32; 16]| drawer.draw_frame(gl, mv, prj)),
+        &CAM_MODEL,
+        &CAM_PROJECTION_Z_FLIP,
+    );
+    check_golden("drawer_array", &img);
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_array_xyrgb() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    // the drawer draws a single triangle in the normalized device coordinate
+    #[rustfmt::skip]
+    let vtx2xyrgb = vec![
+        -0.5, -0.5, 1.0, 0.0, 0.0,
+        0.0, 0.5, 0.0, 1.0, 0.0,
+        0.5, -0.5, 0.0, 0.0, 1.0,
+    ];
+    let mut drawer = del_gl_core::drawer_array_xyrgb::Drawer::new(gl::TRIANGLES);
+    drawer.compile_shader(&gl).unwrap();
+    drawer.initialize(&gl, &vtx2xyrgb);
+    let img = headless.render(
+        &mut DrawFn(|gl: &gl::Gl, _: &[f32; 16], _: &[f32; 16]| drawer.draw_frame(gl)),
+        &CAM_MODEL,
+        &CAM_PROJECTION,
+    );
+    check_golden("drawer_array_xyrgb", &img);
+}
+
+#[test]
+#[ignore = "needs an EGL device"]
+fn drawer_array_xyzuv() {
+    let mut headless = headless();
+    let gl = headless.gl.clone();
+    let mut drawer = del_gl_core::drawer_array_xyzuv::Drawer::new(gl.clone());
+    drawer.init_gl().unwrap();
+    // full screen quad of the texture
+    #[rustfmt::skip]
+    let vtx2xyuv: [f32; 24] = [
+        -1.0, -1.0, 0., 0.,
+        1.0, -1.0, 1., 0.,
+        1.0, 1.0, 1., 1.,
+        //
+        -1.0, -1.0, 0., 0.,
+        1.0, 1.0, 1., 1.,
+        -1.0, 1.0, 0., 1.
+    ];
+    drawer
+        .vbo
+        .set_data(&vtx2xyuv, del_gl_core::buffer::BufferUsage::Static);
+    drawer.tex =
+        del_gl_core::texture::Texture::from_path(&gl, asset_path("spot_texture.png"), false)
+            .unwrap();
+    let img = headless.render(
+        &mut DrawFn(|_: &gl::Gl, _: &[f32; 16], _: &[f32; 16]| {
+            drawer.tex.bind();
+            drawer.draw()
+        }),
+        &CAM_MODEL,
+        &CAM_PROJECTION,
+    );
+    check_golden("drawer_array_xyzuv", &img);
+}
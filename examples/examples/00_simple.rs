@@ -58,7 +58,8 @@ impl MyRenderer {
         let gl = &self.gl;
         unsafe {
             self.program =
-                del_gl_core::set_shader_program(&gl, VERTEX_SHADER_SOURCE, FRAGMENT_SHADER_SOURCE);
+                del_gl_core::set_shader_program(gl, VERTEX_SHADER_SOURCE, FRAGMENT_SHADER_SOURCE)
+                    .unwrap();
             self.vao = std::mem::zeroed();
             gl.GenVertexArrays(1, &mut self.vao);
             gl.BindVertexArray(self.vao);
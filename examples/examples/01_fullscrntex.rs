@@ -1,5 +1,4 @@
 use glutin::display::GlDisplay;
-use image::EncodableLayout;
 use winit::application::ApplicationHandler;
 use winit::event::{KeyEvent, WindowEvent};
 use winit::event_loop::EventLoop;
@@ -8,6 +7,7 @@ use winit::window::Window;
 //
 use del_gl_core::gl;
 use del_gl_core::gl::types::GLfloat;
+use del_gl_core::texture::Texture;
 use del_gl_winit_glutin::app_internal;
 
 pub struct MyApp {
@@ -38,17 +38,17 @@ impl ApplicationHandler for MyApp {
         // WGL.
         self.renderer.get_or_insert_with(|| {
             let gl_display = &app_state.gl_context.display();
-            let gl = gl::Gl::load_with(|symbol| {
+            let gl = std::rc::Rc::new(gl::Gl::load_with(|symbol| {
                 let symbol = std::ffi::CString::new(symbol).unwrap();
                 gl_display.get_proc_address(symbol.as_c_str()).cast()
-            });
+            }));
             let mut render = del_gl_core::drawer_array_xyzuv::Drawer::new(gl);
-            render.init_gl();
+            render.init_gl().unwrap();
             render
         });
         unsafe {
             //
-            let Some(rndr) = &self.renderer else {
+            let Some(rndr) = &mut self.renderer else {
                 panic!();
             };
             let gl = &rndr.gl;
@@ -63,7 +63,7 @@ impl ApplicationHandler for MyApp {
                     1.0, 1.0, 1., 1.,
                     -1.0, 1.0, 0., 1.
                 ];
-                gl.BindBuffer(gl::ARRAY_BUFFER, rndr.vbo);
+                rndr.vbo.bind();
                 gl.BufferData(
                     gl::ARRAY_BUFFER,
                     (VERTEX_DATA.len() * std::mem::size_of::<f32>()) as gl::types::GLsizeiptr,
@@ -71,27 +71,7 @@ impl ApplicationHandler for MyApp {
                     gl::STATIC_DRAW,
                 );
             }
-            {
-                let img = image::ImageReader::open("asset/spot_texture.png").unwrap();
-                println!("{:?}", img.format());
-                let img = img.decode().unwrap().to_rgb8();
-                let img = image::imageops::flip_vertical(&img);
-                println!("{:?}", img.dimensions());
-                gl.BindTexture(gl::TEXTURE_2D, rndr.id_tex);
-                gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
-                gl.TexImage2D(
-                    gl::TEXTURE_2D,
-                    0,
-                    gl::RGB.try_into().unwrap(),
-                    img.width().try_into().unwrap(),
-                    img.height().try_into().unwrap(),
-                    0,
-                    gl::RGB,
-                    gl::UNSIGNED_BYTE,
-                    img.as_bytes().as_ptr() as *const _,
-                );
-                gl.GenerateMipmap(gl::TEXTURE_2D);
-            }
+            rndr.tex = Texture::from_path(gl, "asset/spot_texture.png", false).unwrap();
         }
         assert!(self.appi.state.replace(app_state).is_none());
     }
@@ -1,5 +1,4 @@
 use glutin::display::GlDisplay;
-use image::EncodableLayout;
 use num_traits::cast::AsPrimitive;
 
 //
@@ -37,7 +36,7 @@ void main() {
 \0";
 
 pub struct MyRenderer {
-    gl: gl::Gl,
+    gl: std::rc::Rc<gl::Gl>,
     program: gl::types::GLuint,
     vao: gl::types::GLuint,
     vbo_uv: gl::types::GLuint,
@@ -46,7 +45,7 @@ pub struct MyRenderer {
     loc_tex: gl::types::GLint,
     loc_mat_mvp: gl::types::GLint,
     loc_uv: gl::types::GLint,
-    id_tex: gl::types::GLint,
+    tex: Option<del_gl_core::texture::Texture>,
     ebos: Vec<ElementBufferObject>,
 }
 
@@ -57,7 +56,7 @@ struct ElementBufferObject {
 }
 
 impl MyRenderer {
-    fn new(gl: gl::Gl) -> Self {
+    fn new(gl: std::rc::Rc<gl::Gl>) -> Self {
         Self {
             gl,
             program: 0,
@@ -68,7 +67,7 @@ impl MyRenderer {
             loc_xyz: 0,
             loc_uv: 0,
             loc_mat_mvp: 0,
-            id_tex: -1,
+            tex: None,
             ebos: vec![],
         }
     }
@@ -86,8 +85,8 @@ impl MyRenderer {
             gl.ClearColor(0.3, 0.3, 0.3, 1.0);
             gl.Clear(gl::COLOR_BUFFER_BIT);
             gl.Clear(gl::DEPTH_BUFFER_BIT);
-            if self.id_tex >= 0 {
-                gl.BindTexture(gl::TEXTURE_2D, self.id_tex.try_into().unwrap());
+            if let Some(tex) = &self.tex {
+                tex.bind();
             }
             for ebo in &self.ebos {
                 gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo.ebo);
@@ -113,7 +112,8 @@ impl MyRenderer {
             del_gl_core::print_info(gl);
             gl.Enable(gl::DEPTH_TEST);
             self.program =
-                del_gl_core::set_shader_program(&gl, VERTEX_SHADER_SOURCE, FRAGMENT_SHADER_SOURCE);
+                del_gl_core::set_shader_program(gl, VERTEX_SHADER_SOURCE, FRAGMENT_SHADER_SOURCE)
+                    .unwrap();
             self.loc_xyz = del_gl_core::utility::get_attrib_location(gl, "position", self.program);
             self.loc_uv = del_gl_core::utility::get_attrib_location(gl, "texIn", self.program);
             self.loc_tex =
@@ -204,10 +204,10 @@ impl winit::application::ApplicationHandler for MyApp {
         use glutin::display::GetGlDisplay;
         self.renderer.get_or_insert_with(|| {
             let gl_display = &app_state.gl_context.display();
-            let gl = gl::Gl::load_with(|symbol| {
+            let gl = std::rc::Rc::new(gl::Gl::load_with(|symbol| {
                 let symbol = std::ffi::CString::new(symbol).unwrap();
                 gl_display.get_proc_address(symbol.as_c_str()).cast()
-            });
+            }));
             let mut render: MyRenderer = MyRenderer::new(gl);
             render.init_gl();
             render
@@ -217,12 +217,6 @@ impl winit::application::ApplicationHandler for MyApp {
             obj.load("asset/spot_triangulated.obj").unwrap();
             obj.unified_xyz_uv_as_trimesh()
         };
-        //println!("{:?}",img.color());
-        let img_tex = image::ImageReader::open("asset/spot_texture.png").unwrap();
-        println!("{:?}", img_tex.format());
-        let img_tex = img_tex.decode().unwrap().to_rgb8();
-        let img_tex = image::imageops::flip_vertical(&img_tex);
-        println!("{:?}", img_tex.dimensions());
         //
         unsafe {
             if let Some(rndr) = &mut self.renderer {
@@ -279,25 +273,11 @@ impl winit::application::ApplicationHandler for MyApp {
                     ebo: ebo0,
                 });
                 //
-                gl.Enable(gl::TEXTURE_2D);
                 gl.ActiveTexture(gl::TEXTURE0);
-                let mut id_tex: gl::types::GLuint = 0;
-                gl.GenTextures(1, &mut id_tex);
-                rndr.id_tex = id_tex as i32;
-                gl.BindTexture(gl::TEXTURE_2D, rndr.id_tex.try_into().unwrap());
-                gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
-                gl.TexImage2D(
-                    gl::TEXTURE_2D,
-                    0,
-                    gl::RGB.try_into().unwrap(),
-                    img_tex.width().try_into().unwrap(),
-                    img_tex.height().try_into().unwrap(),
-                    0,
-                    gl::RGB,
-                    gl::UNSIGNED_BYTE,
-                    img_tex.as_bytes().as_ptr() as *const _,
+                rndr.tex = Some(
+                    del_gl_core::texture::Texture::from_path(gl, "asset/spot_texture.png", false)
+                        .unwrap(),
                 );
-                gl.GenerateMipmap(gl::TEXTURE_2D);
             }
         }
         assert!(self.appi.state.replace(app_state).is_none());
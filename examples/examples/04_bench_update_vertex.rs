@@ -0,0 +1,100 @@
+//! per-frame cost of updating the vertices of a 1M-vertex mesh.
+//! run with `static`, `dynamic` or `stream` as the argument to select the usage hint
+
+use del_gl_core::gl;
+use std::rc::Rc;
+
+struct MyViewTrg {
+    drawer: del_gl_core::drawer_mesh::Drawer,
+    vtx2xyz: Vec<f32>,
+    iframe: usize,
+    time_update: std::time::Duration,
+}
+
+const NDIV: usize = 1000;
+const NFRAME_REPORT: usize = 100;
+
+impl del_gl_winit_glutin::viewer3d_for_gl_renderer::GlRenderer for MyViewTrg {
+    fn draw(&mut self, gl: &Rc<gl::Gl>, cam_model: &[f32; 16], cam_projection: &[f32; 16]) {
+        let t = self.iframe as f32 * 0.05;
+        for (ivtx, xyz) in self.vtx2xyz.chunks_mut(3).enumerate() {
+            let (i, j) = (ivtx % (NDIV + 1), ivtx / (NDIV + 1));
+            xyz[2] = 0.05 * ((i as f32) * 0.02 + t).sin() * ((j as f32) * 0.02 + t).cos();
+        }
+        let time = std::time::Instant::now();
+        self.drawer.update_vertex(gl, &self.vtx2xyz, 3);
+        self.time_update += time.elapsed();
+        self.drawer.draw(gl, cam_model, cam_projection);
+        self.iframe += 1;
+        if self.iframe.is_multiple_of(NFRAME_REPORT) {
+            println!(
+                "{:?}: update_vertex {:.3} ms/frame ({} vertices)",
+                self.drawer.usage,
+                self.time_update.as_secs_f64() * 1000.0 / NFRAME_REPORT as f64,
+                self.vtx2xyz.len() / 3
+            );
+            self.time_update = std::time::Duration::ZERO;
+        }
+    }
+
+    fn initialize(&mut self, gl: &Rc<gl::Gl>) {
+        self.drawer.compile_shader(gl).unwrap();
+        let mut tri2vtx = Vec::<usize>::with_capacity(NDIV * NDIV * 6);
+        for j in 0..NDIV {
+            for i in 0..NDIV {
+                let i0 = j * (NDIV + 1) + i;
+                let i1 = i0 + 1;
+                let i2 = i0 + NDIV + 1;
+                let i3 = i2 + 1;
+                tri2vtx.extend_from_slice(&[i0, i1, i3, i0, i3, i2]);
+            }
+        }
+        self.drawer.update_vertex(gl, &self.vtx2xyz, 3);
+        self.drawer
+            .add_element(gl, gl::TRIANGLES, &tri2vtx, [0.8, 0.4, 0.2]);
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    use del_gl_core::buffer::BufferUsage;
+    let usage = match std::env::args().nth(1).as_deref() {
+        Some("static") => BufferUsage::Static,
+        Some("stream") => BufferUsage::Stream,
+        _ => BufferUsage::Dynamic,
+    };
+    let mut vtx2xyz = Vec::<f32>::with_capacity((NDIV + 1) * (NDIV + 1) * 3);
+    for j in 0..NDIV + 1 {
+        for i in 0..NDIV + 1 {
+            let x = i as f32 / NDIV as f32 - 0.5;
+            let y = j as f32 / NDIV as f32 - 0.5;
+            vtx2xyz.extend_from_slice(&[x, y, 0.]);
+        }
+    }
+    let mut drawer = del_gl_core::drawer_mesh::Drawer::new();
+    drawer.usage = usage;
+    let window_attributes = winit::window::Window::default_attributes()
+        .with_transparent(false)
+        .with_title("04_bench_update_vertex")
+        .with_inner_size(winit::dpi::PhysicalSize {
+            width: 600,
+            height: 600,
+        });
+    let template = glutin::config::ConfigTemplateBuilder::new()
+        .with_alpha_size(8)
+        .with_transparency(cfg!(cgl_backend));
+    let display_builder =
+        glutin_winit::DisplayBuilder::new().with_window_attributes(Some(window_attributes));
+    let mut app = del_gl_winit_glutin::viewer3d_for_gl_renderer::Viewer3d::new(
+        template,
+        display_builder,
+        Box::new(MyViewTrg {
+            drawer,
+            vtx2xyz,
+            iframe: 0,
+            time_update: std::time::Duration::ZERO,
+        }),
+    );
+    let event_loop = winit::event_loop::EventLoop::new().unwrap();
+    event_loop.run_app(&mut app)?;
+    app.appi.exit_state
+}
@@ -0,0 +1,80 @@
+//! render a mesh to a PNG file without a window.
+//! usage: `05_headless_render [input.obj] [output.png] [size]`
+
+use del_gl_core::gl;
+use del_gl_winit_glutin::viewer3d_for_gl_renderer::GlRenderer;
+use std::rc::Rc;
+
+struct MyViewTrg {
+    drawer: del_gl_core::drawer_mesh::Drawer,
+    path_obj: String,
+}
+
+impl GlRenderer for MyViewTrg {
+    fn draw(&mut self, gl: &Rc<gl::Gl>, cam_model: &[f32; 16], cam_projection: &[f32; 16]) {
+        self.drawer.draw(gl, cam_model, cam_projection);
+    }
+
+    fn initialize(&mut self, gl: &Rc<gl::Gl>) {
+        self.drawer.compile_shader(gl).unwrap();
+        let (tri2vtx, vtx2xyz) = {
+            let mut obj = del_msh_core::io_obj::WavefrontObj::<usize, f32>::new();
+            obj.load(&self.path_obj).unwrap();
+            (obj.idx2vtx_xyz, obj.vtx2xyz)
+        };
+        let edge2vtx = del_msh_core::edge2vtx::from_triangle_mesh(&tri2vtx, vtx2xyz.len() / 3);
+        self.drawer.update_vertex(gl, &vtx2xyz, 3);
+        let i_tri = self
+            .drawer
+            .add_element(gl, gl::TRIANGLES, &tri2vtx, [1.0, 0.0, 0.0]);
+        // push the triangles back so that the edges on them do not flicker
+        self.drawer.render_state_mut(i_tri).unwrap().polygon_offset = Some([1.0, 1.0]);
+        self.drawer
+            .add_element(gl, gl::LINES, &edge2vtx, [0.0, 0.0, 0.0]);
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    let path_obj = args
+        .get(1)
+        .cloned()
+        .unwrap_or("asset/spot_triangulated.obj".to_string());
+    let path_png = args
+        .get(2)
+        .cloned()
+        .unwrap_or("target/05_headless_render.png".to_string());
+    let size: usize = args.get(3).map_or(Ok(600), |s| s.parse())?;
+    //
+    let mut headless = del_gl_winit_glutin::headless::Headless::new(size, size)?;
+    del_gl_core::print_info(&headless.gl);
+    let mut renderer = MyViewTrg {
+        drawer: del_gl_core::drawer_mesh::Drawer::new(),
+        path_obj,
+    };
+    renderer.initialize(&headless.gl);
+    // same camera as `Viewer3d`
+    let view_rot = del_geo_core::view_rotation::Trackball::new();
+    let view_prj = del_geo_core::view_projection::Perspective {
+        lens: 24.,
+        near: 0.5,
+        far: 3.0,
+        cam_pos: [0., 0., 2.],
+        proj_direction: false,
+        scale: 1.,
+    };
+    let img = headless.render(
+        &mut renderer,
+        &view_rot.mat4_col_major(),
+        &view_prj.mat4_col_major(1.0),
+    );
+    image::save_buffer(
+        &path_png,
+        &img,
+        size as u32,
+        size as u32,
+        image::ExtendedColorType::Rgb8,
+    )?;
+    println!("saved {}", path_png);
+    Ok(())
+}